@@ -0,0 +1,208 @@
+use tokio::sync::{mpsc, oneshot};
+
+use super::{AudioData, AudioRecorder, RecorderEvents};
+use crate::config::AudioConfig;
+use crate::error::{AppError, Result};
+
+/// Requests handled by the audio thread
+enum Command {
+    Configure(AudioConfig, oneshot::Sender<Result<()>>),
+    Start(oneshot::Sender<Result<RecorderEvents>>),
+    Stop(oneshot::Sender<Result<AudioData>>),
+    Pause(oneshot::Sender<Result<()>>),
+    Resume(oneshot::Sender<Result<()>>),
+    Cancel(oneshot::Sender<()>),
+    Reconnect(oneshot::Sender<Result<String>>),
+    Abort(oneshot::Sender<Result<Option<AudioData>>>),
+}
+
+/// Handle to the audio recorder running on its own OS thread
+///
+/// `cpal::Stream` is `!Send`, so the recorder and its stream never leave the
+/// thread that created them; commands reach it over a channel instead. The
+/// handle is cheap to clone and the thread exits once every handle is dropped.
+#[derive(Clone)]
+pub struct RecorderHandle {
+    commands: mpsc::UnboundedSender<Command>,
+}
+
+impl RecorderHandle {
+    /// Start the audio thread
+    ///
+    /// No device is opened until the first [`configure`](Self::configure).
+    pub fn spawn() -> Result<Self> {
+        let (commands, receiver) = mpsc::unbounded_channel();
+
+        std::thread::Builder::new()
+            .name("audio-recorder".to_string())
+            .spawn(move || run(receiver))
+            .map_err(|e| {
+                AppError::AudioRecording(format!("Failed to start audio thread: {}", e))
+            })?;
+
+        Ok(Self { commands })
+    }
+
+    /// Create the recorder, or apply new audio settings to it
+    ///
+    /// The recorder is recreated when the input device or capture mode changes.
+    pub async fn configure(&self, config: &AudioConfig) -> Result<()> {
+        let config = config.clone();
+        self.request(|reply| Command::Configure(config, reply))
+            .await?
+    }
+
+    /// Start recording; events for the recording arrive on the returned channel
+    pub async fn start(&self) -> Result<RecorderEvents> {
+        self.request(Command::Start).await?
+    }
+
+    /// Stop recording and return the prepared audio
+    pub async fn stop(&self) -> Result<AudioData> {
+        self.request(Command::Stop).await?
+    }
+
+    pub async fn pause(&self) -> Result<()> {
+        self.request(Command::Pause).await?
+    }
+
+    pub async fn resume(&self) -> Result<()> {
+        self.request(Command::Resume).await?
+    }
+
+    /// Stop recording and discard the audio
+    pub async fn cancel(&self) -> Result<()> {
+        self.request(Command::Cancel).await
+    }
+
+    /// Reopen the input after a stream error; returns the device now in use
+    pub async fn reconnect(&self) -> Result<String> {
+        self.request(Command::Reconnect).await?
+    }
+
+    /// End the recording after a stream error, returning the prepared audio captured so far
+    pub async fn abort(&self) -> Result<Option<AudioData>> {
+        self.request(Command::Abort).await?
+    }
+
+    async fn request<T>(&self, command: impl FnOnce(oneshot::Sender<T>) -> Command) -> Result<T> {
+        let (reply, response) = oneshot::channel();
+        self.commands
+            .send(command(reply))
+            .map_err(|_| thread_stopped())?;
+        response.await.map_err(|_| thread_stopped())
+    }
+}
+
+fn thread_stopped() -> AppError {
+    AppError::AudioRecording("Audio thread is not running".to_string())
+}
+
+/// Body of the audio thread: owns the recorder and serves commands until
+/// every handle is dropped
+fn run(mut commands: mpsc::UnboundedReceiver<Command>) {
+    let mut recorder: Option<AudioRecorder> = None;
+    let no_recorder = || AppError::InvalidState("No active recorder".to_string());
+
+    while let Some(command) = commands.blocking_recv() {
+        // Replies are dropped silently if the caller has gone away
+        match command {
+            Command::Configure(config, reply) => {
+                let result = match recorder.as_mut() {
+                    Some(existing) if existing.uses_input_of(&config) => {
+                        existing.apply_config(&config);
+                        Ok(())
+                    }
+                    _ => {
+                        // Close the old device before opening the new one
+                        recorder = None;
+                        AudioRecorder::new(&config).map(|created| recorder = Some(created))
+                    }
+                };
+                reply.send(result).ok();
+            }
+            Command::Start(reply) => {
+                let result = recorder
+                    .as_mut()
+                    .ok_or_else(no_recorder)
+                    .and_then(|r| r.start_recording());
+                reply.send(result).ok();
+            }
+            Command::Stop(reply) => {
+                let result = recorder
+                    .as_mut()
+                    .ok_or_else(no_recorder)
+                    .and_then(|r| r.stop_recording());
+                reply.send(result).ok();
+            }
+            Command::Pause(reply) => {
+                let result = recorder
+                    .as_mut()
+                    .ok_or_else(no_recorder)
+                    .and_then(|r| r.pause_recording());
+                reply.send(result).ok();
+            }
+            Command::Resume(reply) => {
+                let result = recorder
+                    .as_mut()
+                    .ok_or_else(no_recorder)
+                    .and_then(|r| r.resume_recording());
+                reply.send(result).ok();
+            }
+            Command::Cancel(reply) => {
+                if let Some(recorder) = recorder.as_mut() {
+                    recorder.cancel_recording();
+                }
+                reply.send(()).ok();
+            }
+            Command::Reconnect(reply) => {
+                let result = recorder
+                    .as_mut()
+                    .ok_or_else(no_recorder)
+                    .and_then(|r| r.reconnect());
+                reply.send(result).ok();
+            }
+            Command::Abort(reply) => {
+                let result = match recorder.as_mut() {
+                    Some(recorder) => recorder.abort_recording(),
+                    None => Ok(None),
+                };
+                reply.send(result).ok();
+            }
+        }
+    }
+
+    log::info!("Audio thread stopped");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_commands_before_configure_fail_cleanly() {
+        let handle = RecorderHandle::spawn().unwrap();
+
+        assert!(matches!(
+            handle.start().await,
+            Err(AppError::InvalidState(_))
+        ));
+        assert!(matches!(
+            handle.stop().await,
+            Err(AppError::InvalidState(_))
+        ));
+        assert!(matches!(
+            handle.pause().await,
+            Err(AppError::InvalidState(_))
+        ));
+
+        // The thread keeps serving requests after errors
+        assert!(handle.cancel().await.is_ok());
+    }
+
+    #[test]
+    fn test_handle_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<RecorderHandle>();
+    }
+}
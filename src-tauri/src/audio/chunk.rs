@@ -0,0 +1,153 @@
+use super::vad::frame_energy_db;
+use super::AudioData;
+
+/// Length of the frames compared when looking for a quiet place to cut
+const FRAME_MS: u32 = 20;
+
+/// A piece of a longer recording
+#[derive(Debug, Clone)]
+pub struct Chunk {
+    pub audio: AudioData,
+    /// Where the chunk starts in the original audio
+    pub start_seconds: f32,
+}
+
+impl Chunk {
+    pub fn end_seconds(&self) -> f32 {
+        self.start_seconds + self.audio.duration_seconds()
+    }
+}
+
+/// Split long audio into overlapping chunks of at most `max_seconds`
+///
+/// Each cut is placed at the quietest frame in the last third of the chunk so
+/// words are rarely split, and the next chunk starts `overlap_ms` before the
+/// cut. Audio that already fits, or `max_seconds == 0`, comes back whole.
+pub fn split_at_silence(audio: &AudioData, max_seconds: u32, overlap_ms: u32) -> Vec<Chunk> {
+    let channels = audio.channels.max(1) as usize;
+    let rate = audio.sample_rate as usize;
+    let total = audio.samples.len() / channels;
+    let max_frames = max_seconds as usize * rate;
+
+    if max_frames == 0 || total <= max_frames {
+        return vec![Chunk {
+            audio: audio.clone(),
+            start_seconds: 0.0,
+        }];
+    }
+
+    // Keep the overlap well below the chunk length so every chunk moves forward
+    let overlap = (rate * overlap_ms as usize / 1000).min(max_frames / 2);
+    let window = (rate * FRAME_MS as usize / 1000).max(1);
+
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    loop {
+        if total - start <= max_frames {
+            chunks.push(slice(audio, start, total));
+            break;
+        }
+
+        let limit = start + max_frames;
+        let cut = quietest_point(audio, start + max_frames * 2 / 3, limit, window);
+        chunks.push(slice(audio, start, cut));
+        start = cut - overlap;
+    }
+
+    log::info!(
+        "Split {:.2}s of audio into {} chunks",
+        audio.duration_seconds(),
+        chunks.len()
+    );
+    chunks
+}
+
+/// Middle of the quietest frame between `from` and `to`, in frames
+fn quietest_point(audio: &AudioData, from: usize, to: usize, window: usize) -> usize {
+    let channels = audio.channels.max(1) as usize;
+    let mut best = (f32::INFINITY, to);
+
+    let mut position = from;
+    while position + window <= to {
+        let frame = &audio.samples[position * channels..(position + window) * channels];
+        let energy = frame_energy_db(frame);
+        // Later frames win ties so chunks stay as long as possible
+        if energy <= best.0 {
+            best = (energy, position + window / 2);
+        }
+        position += window;
+    }
+
+    best.1
+}
+
+fn slice(audio: &AudioData, start: usize, end: usize) -> Chunk {
+    let channels = audio.channels.max(1) as usize;
+    Chunk {
+        audio: AudioData::new(
+            audio.samples[start * channels..end * channels].to_vec(),
+            audio.sample_rate,
+            audio.channels,
+        ),
+        start_seconds: start as f32 / audio.sample_rate.max(1) as f32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 1000;
+
+    fn tone(seconds: f32) -> Vec<f32> {
+        (0..(RATE as f32 * seconds) as usize)
+            .map(|i| (i as f32 * 0.7).sin() * 0.5)
+            .collect()
+    }
+
+    #[test]
+    fn test_short_audio_is_not_split() {
+        let audio = AudioData::new(tone(5.0), RATE, 1);
+        let chunks = split_at_silence(&audio, 10, 500);
+
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].audio.samples.len(), audio.samples.len());
+        assert_eq!(chunks[0].start_seconds, 0.0);
+        assert_eq!(split_at_silence(&audio, 0, 500).len(), 1);
+    }
+
+    #[test]
+    fn test_cuts_in_silent_gap_with_overlap() {
+        // Speech, a pause from 8 to 8.5 s, then more speech
+        let mut samples = tone(8.0);
+        samples.extend(vec![0.0; 500]);
+        samples.extend(tone(6.5));
+        let audio = AudioData::new(samples, RATE, 1);
+
+        let chunks = split_at_silence(&audio, 10, 200);
+
+        assert_eq!(chunks.len(), 2);
+        let first = chunks[0].audio.samples.len();
+        assert!((8000..8500).contains(&first), "cut at {}", first);
+
+        // The second chunk repeats the overlap and runs to the end
+        assert_eq!(first - 200 + chunks[1].audio.samples.len(), audio.samples.len());
+        assert_eq!(chunks[1].start_seconds, (first - 200) as f32 / RATE as f32);
+        assert!((chunks[1].end_seconds() - audio.duration_seconds()).abs() < 1e-3);
+        assert!(chunks.iter().all(|c| c.audio.duration_seconds() <= 10.0));
+    }
+
+    #[test]
+    fn test_stereo_chunks_keep_whole_frames() {
+        let samples: Vec<f32> = tone(25.0).iter().flat_map(|&s| [s, -s]).collect();
+        let audio = AudioData::new(samples, RATE, 2);
+
+        let chunks = split_at_silence(&audio, 10, 1000);
+
+        assert!(chunks.len() >= 3);
+        assert!(chunks
+            .iter()
+            .all(|c| c.audio.samples.len() % 2 == 0 && c.audio.channels == 2));
+        assert!(chunks.iter().all(|c| c.audio.duration_seconds() <= 10.0));
+    }
+}
@@ -0,0 +1,97 @@
+use hound::{WavSpec, WavWriter};
+use std::io::{Cursor, Seek, Write};
+use std::path::Path;
+
+use crate::error::{AppError, Result};
+
+/// Recorded audio held in memory as interleaved f32 samples
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioData {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+impl AudioData {
+    pub fn new(samples: Vec<f32>, sample_rate: u32, channels: u16) -> Self {
+        Self {
+            samples,
+            sample_rate,
+            channels,
+        }
+    }
+
+    /// Length of the audio in seconds
+    pub fn duration_seconds(&self) -> f32 {
+        let samples_per_second = self.sample_rate as f32 * self.channels.max(1) as f32;
+        if samples_per_second == 0.0 {
+            return 0.0;
+        }
+        self.samples.len() as f32 / samples_per_second
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Encode as a 16-bit PCM WAV file in memory
+    pub fn to_wav_bytes(&self) -> Result<Vec<u8>> {
+        let mut cursor = Cursor::new(Vec::new());
+        self.write_wav(&mut cursor)?;
+        Ok(cursor.into_inner())
+    }
+
+    /// Write as a 16-bit PCM WAV file on disk
+    pub fn save_wav(&self, path: &Path) -> Result<()> {
+        let file = std::fs::File::create(path)?;
+        self.write_wav(std::io::BufWriter::new(file))
+    }
+
+    fn write_wav<W: Write + Seek>(&self, writer: W) -> Result<()> {
+        let spec = WavSpec {
+            channels: self.channels,
+            sample_rate: self.sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+
+        let mut writer = WavWriter::new(writer, spec)
+            .map_err(|e| AppError::AudioRecording(format!("Failed to create WAV file: {}", e)))?;
+
+        for &sample in &self.samples {
+            writer
+                .write_sample(super::encode::to_i16(sample))
+                .map_err(|e| AppError::AudioRecording(format!("Failed to write sample: {}", e)))?;
+        }
+
+        writer
+            .finalize()
+            .map_err(|e| AppError::AudioRecording(format!("Failed to finalize WAV file: {}", e)))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duration() {
+        let audio = AudioData::new(vec![0.0; 32000], 16000, 2);
+        assert_eq!(audio.duration_seconds(), 1.0);
+    }
+
+    #[test]
+    fn test_wav_bytes_round_trip() {
+        let audio = AudioData::new(vec![0.0, 0.5, -0.5, 1.0, -1.0, 1.5], 16000, 1);
+
+        let bytes = audio.to_wav_bytes().unwrap();
+        let mut reader = hound::WavReader::new(Cursor::new(bytes)).unwrap();
+
+        assert_eq!(reader.spec().sample_rate, 16000);
+        assert_eq!(reader.spec().channels, 1);
+        let samples: Vec<i16> = reader.samples::<i16>().map(|s| s.unwrap()).collect();
+        assert_eq!(samples, vec![0, 16384, -16384, i16::MAX, i16::MIN, i16::MAX]);
+    }
+}
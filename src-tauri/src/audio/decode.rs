@@ -0,0 +1,317 @@
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL, CODEC_TYPE_OPUS};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::{FormatOptions, FormatReader, Track};
+use symphonia::core::io::{MediaSource, MediaSourceStream};
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+use super::AudioData;
+use crate::error::{AppError, Result};
+
+/// Largest Opus frame: 120 ms at 48 kHz
+const OPUS_MAX_FRAME: usize = 5760;
+
+/// Largest Ogg page: 27 byte header, 255 lacing values and 255 * 255 bytes of data
+const OGG_MAX_PAGE: u64 = 65307;
+
+/// Decode a WAV, FLAC, MP3 or Ogg (Vorbis or Opus) file to interleaved f32 samples
+///
+/// The audio keeps the file's own sample rate and channel count.
+pub fn decode_file(path: &Path) -> Result<AudioData> {
+    let file = std::fs::File::open(path)?;
+    let source = MediaSourceStream::new(Box::new(file), Default::default());
+    let extension = path.extension().and_then(|e| e.to_str());
+
+    decode_stream(source, extension, &path.display().to_string())
+}
+
+/// Decode audio held in memory, e.g. a decrypted file
+pub fn decode_bytes(bytes: Vec<u8>, extension: &str) -> Result<AudioData> {
+    let source = MediaSourceStream::new(Box::new(Cursor::new(bytes)), Default::default());
+    decode_stream(source, Some(extension), "audio buffer")
+}
+
+fn decode_stream(
+    source: MediaSourceStream,
+    extension: Option<&str>,
+    name: &str,
+) -> Result<AudioData> {
+    let mut hint = Hint::new();
+    if let Some(extension) = extension {
+        hint.with_extension(extension);
+    }
+
+    let probed = symphonia::default::get_probe()
+        .format(
+            &hint,
+            source,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .map_err(|e| AppError::AudioDecoding(format!("Unsupported audio file: {}", e)))?;
+    let mut format = probed.format;
+
+    let track = format
+        .tracks()
+        .iter()
+        .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+        .cloned()
+        .ok_or_else(|| AppError::AudioDecoding("No audio track in file".to_string()))?;
+
+    let audio = if track.codec_params.codec == CODEC_TYPE_OPUS {
+        decode_opus(format, &track)?
+    } else {
+        decode_track(format.as_mut(), &track)?
+    };
+
+    log::info!(
+        "Decoded {}: {:.2}s at {} Hz, {} channels",
+        name,
+        audio.duration_seconds(),
+        audio.sample_rate,
+        audio.channels
+    );
+    Ok(audio)
+}
+
+/// Decode a track with one of symphonia's built-in codecs
+fn decode_track(format: &mut dyn FormatReader, track: &Track) -> Result<AudioData> {
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(|e| AppError::AudioDecoding(format!("Unsupported codec: {}", e)))?;
+
+    let mut samples = Vec::new();
+    let mut sample_rate = track.codec_params.sample_rate;
+    let mut channels = track.codec_params.channels.map(|c| c.count() as u16);
+
+    while let Some(packet) = next_packet(format, track.id)? {
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            // A corrupt packet is skipped rather than failing the whole file
+            Err(SymphoniaError::DecodeError(e)) => {
+                log::warn!("Skipping undecodable packet: {}", e);
+                continue;
+            }
+            Err(e) => return Err(AppError::AudioDecoding(format!("Decoding failed: {}", e))),
+        };
+
+        let spec = *decoded.spec();
+        sample_rate.get_or_insert(spec.rate);
+        channels.get_or_insert(spec.channels.count() as u16);
+
+        let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        buffer.copy_interleaved_ref(decoded);
+        samples.extend_from_slice(buffer.samples());
+    }
+
+    let sample_rate = sample_rate
+        .ok_or_else(|| AppError::AudioDecoding("Unknown sample rate".to_string()))?;
+    let channels = channels.unwrap_or(1);
+
+    // Drop padding in the final block beyond the length the container declares
+    if let Some(frames) = track.codec_params.n_frames {
+        samples.truncate(frames as usize * channels as usize);
+    }
+
+    Ok(AudioData::new(samples, sample_rate, channels))
+}
+
+/// Decode an Ogg Opus track, which symphonia can demux but not decode
+///
+/// The encoder delay and the padding after the end are cut off as the stream
+/// header and final granule position ask.
+fn decode_opus(mut format: Box<dyn FormatReader>, track: &Track) -> Result<AudioData> {
+    let channels = track.codec_params.channels.map_or(1, |c| c.count());
+    let opus_channels = match channels {
+        1 => opus::Channels::Mono,
+        2 => opus::Channels::Stereo,
+        n => {
+            return Err(AppError::AudioDecoding(format!(
+                "Opus files with {} channels are not supported",
+                n
+            )))
+        }
+    };
+
+    let opus_err = |e: opus::Error| AppError::AudioDecoding(format!("Opus decoding failed: {}", e));
+    let mut decoder = opus::Decoder::new(48000, opus_channels).map_err(opus_err)?;
+    let mut frame = vec![0.0; OPUS_MAX_FRAME * channels];
+    let mut samples = Vec::new();
+
+    while let Some(packet) = next_packet(format.as_mut(), track.id)? {
+        match decoder.decode_float(&packet.data, &mut frame, false) {
+            Ok(frames) => samples.extend_from_slice(&frame[..frames * channels]),
+            // A corrupt packet is skipped rather than failing the whole file
+            Err(e) => log::warn!("Skipping undecodable packet: {}", e),
+        }
+    }
+
+    // Drop the encoder delay the stream asks us to skip
+    let pre_skip = opus_pre_skip(track) as usize;
+    samples.drain(..(pre_skip * channels).min(samples.len()));
+
+    // The final granule position counts the pre-skip and ends at the true length
+    if let Some(granule) = final_granule(format.into_inner()) {
+        let frames = granule.saturating_sub(pre_skip as u64) as usize;
+        samples.truncate(frames * channels);
+    }
+
+    Ok(AudioData::new(samples, 48000, channels as u16))
+}
+
+/// Pre-skip from the OpusHead packet (RFC 7845, section 5.1)
+fn opus_pre_skip(track: &Track) -> u16 {
+    match track.codec_params.extra_data.as_deref() {
+        Some([b'O', b'p', b'u', b's', b'H', b'e', b'a', b'd', _, _, low, high, ..]) => {
+            u16::from_le_bytes([*low, *high])
+        }
+        _ => track.codec_params.delay.unwrap_or(0) as u16,
+    }
+}
+
+/// Granule position of the last Ogg page, read from the end of the stream
+fn final_granule(mut source: MediaSourceStream) -> Option<u64> {
+    let len = source.byte_len()?;
+    source
+        .seek(SeekFrom::Start(len.saturating_sub(OGG_MAX_PAGE)))
+        .ok()?;
+    let mut tail = Vec::new();
+    source.read_to_end(&mut tail).ok()?;
+
+    // Capture pattern followed by stream structure version 0
+    let page = tail.windows(5).rposition(|w| w == b"OggS\0")?;
+    let granule = i64::from_le_bytes(tail.get(page + 6..page + 14)?.try_into().ok()?);
+    // -1 marks a page on which no packet ends
+    u64::try_from(granule).ok()
+}
+
+/// Next packet of the given track, or `None` at the end of the file
+fn next_packet(
+    format: &mut dyn FormatReader,
+    track_id: u32,
+) -> Result<Option<symphonia::core::formats::Packet>> {
+    loop {
+        match format.next_packet() {
+            Ok(packet) if packet.track_id() == track_id => return Ok(Some(packet)),
+            Ok(_) => continue,
+            Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                return Ok(None)
+            }
+            Err(e) => return Err(AppError::AudioDecoding(format!("Failed to read file: {}", e))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::encode;
+
+    fn tone(rate: u32, seconds: f32) -> Vec<f32> {
+        (0..(rate as f32 * seconds) as usize)
+            .map(|i| (2.0 * std::f32::consts::PI * 440.0 * i as f32 / rate as f32).sin() * 0.5)
+            .collect()
+    }
+
+    #[test]
+    fn test_decode_wav() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("memo.wav");
+        let audio = AudioData::new(tone(22050, 0.5), 22050, 1);
+        audio.save_wav(&path).unwrap();
+
+        let decoded = decode_file(&path).unwrap();
+
+        assert_eq!(decoded.sample_rate, 22050);
+        assert_eq!(decoded.channels, 1);
+        assert_eq!(decoded.samples.len(), audio.samples.len());
+        assert!((decoded.samples[100] - audio.samples[100]).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_decode_flac() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("memo.flac");
+        let stereo: Vec<f32> = tone(16000, 0.5).iter().flat_map(|&s| [s, -s]).collect();
+        let audio = AudioData::new(stereo, 16000, 2);
+        std::fs::write(&path, encode::encode_flac(&audio).unwrap()).unwrap();
+
+        let decoded = decode_file(&path).unwrap();
+
+        assert_eq!(decoded.sample_rate, 16000);
+        assert_eq!(decoded.channels, 2);
+        assert_eq!(decoded.samples.len(), audio.samples.len());
+        assert!((decoded.samples[201] + decoded.samples[200]).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_decode_ogg_opus() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("memo.ogg");
+        let audio = AudioData::new(tone(16000, 1.0), 16000, 1);
+        std::fs::write(&path, encode::encode_opus(&audio).unwrap()).unwrap();
+
+        let decoded = decode_file(&path).unwrap();
+
+        assert_eq!(decoded.sample_rate, 48000);
+        assert_eq!(decoded.channels, 1);
+        // Encoder delay and end padding are both cut off
+        assert_eq!(decoded.samples.len(), 48000);
+        let expected = tone(48000, 1.0);
+        let error = decoded.samples[2000..46000]
+            .iter()
+            .zip(&expected[2000..46000])
+            .map(|(a, b)| (a - b).powi(2))
+            .sum::<f32>()
+            / 44000.0;
+        assert!(error < 0.01, "mean squared error {}", error);
+    }
+
+    #[test]
+    fn test_corrupt_opus_packet_is_skipped() {
+        let audio = AudioData::new(tone(16000, 1.0), 16000, 1);
+        let encoded = encode::encode_opus(&audio).unwrap();
+
+        // Rewrite the stream with one packet replaced by an invalid one
+        let mut reader = ogg::reading::PacketReader::new(Cursor::new(encoded));
+        let mut writer = ogg::writing::PacketWriter::new(Vec::new());
+        let mut index = 0;
+        while let Some(packet) = reader.read_packet().unwrap() {
+            let end_info = if packet.last_in_stream() {
+                ogg::writing::PacketWriteEndInfo::EndStream
+            } else if packet.last_in_page() {
+                ogg::writing::PacketWriteEndInfo::EndPage
+            } else {
+                ogg::writing::PacketWriteEndInfo::NormalPacket
+            };
+            let data = if index == 20 {
+                // Code 3 packet claiming zero frames
+                vec![0x03, 0x00]
+            } else {
+                packet.data.clone()
+            };
+            writer
+                .write_packet(data, packet.stream_serial(), end_info, packet.absgp_page())
+                .unwrap();
+            index += 1;
+        }
+
+        let decoded = decode_bytes(writer.into_inner(), "ogg").unwrap();
+        // One 20 ms frame is missing, partly made up for by the end padding
+        let len = decoded.samples.len();
+        assert!((48000 - 960..48000).contains(&len), "decoded {} samples", len);
+    }
+
+    #[test]
+    fn test_rejects_non_audio_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        std::fs::write(&path, "not audio").unwrap();
+
+        assert!(matches!(decode_file(&path), Err(AppError::AudioDecoding(_))));
+    }
+}
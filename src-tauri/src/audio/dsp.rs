@@ -0,0 +1,355 @@
+use crate::config::{AudioConfig, NormalizeMode};
+
+/// Most a recording is ever amplified, so silence-only audio is not blown up
+const MAX_GAIN_DB: f32 = 30.0;
+/// Attenuation applied while the noise gate is closed
+const GATE_FLOOR_DB: f32 = -40.0;
+/// Gate analysis frame
+const GATE_FRAME_MS: u32 = 10;
+/// How long the gate stays open after the level drops below the threshold
+const GATE_HOLD_MS: u32 = 150;
+/// Gate gain smoothing when opening and closing
+const GATE_ATTACK_MS: f32 = 2.0;
+const GATE_RELEASE_MS: f32 = 60.0;
+/// Level above which the soft clipper starts to compress
+const SOFT_CLIP_KNEE: f32 = 0.9;
+/// BS.1770 gating block length and step
+const LOUDNESS_BLOCK_MS: u32 = 400;
+const LOUDNESS_STEP_MS: u32 = 100;
+/// BS.1770 absolute and relative gates
+const LOUDNESS_ABSOLUTE_GATE: f64 = -70.0;
+const LOUDNESS_RELATIVE_GATE: f64 = -10.0;
+
+/// Preprocessing applied to finished recordings before they are encoded
+///
+/// Stages run in order: high-pass filter, noise gate, normalization and soft
+/// clipping. Each one can be switched off in the audio config.
+#[derive(Debug, Clone)]
+pub struct DspChain {
+    highpass_cutoff_hz: Option<f32>,
+    gate_threshold_db: Option<f32>,
+    normalize: Option<NormalizeMode>,
+    peak_target_db: f32,
+    loudness_target_lufs: f32,
+    soft_clip: bool,
+}
+
+impl DspChain {
+    pub fn from_config(config: &AudioConfig) -> Self {
+        Self {
+            highpass_cutoff_hz: config.highpass_enabled.then_some(config.highpass_cutoff_hz),
+            gate_threshold_db: config
+                .noise_gate_enabled
+                .then_some(config.noise_gate_threshold_db),
+            normalize: config.normalize_enabled.then_some(config.normalize_mode),
+            peak_target_db: config.normalize_peak_db,
+            loudness_target_lufs: config.normalize_lufs,
+            soft_clip: config.soft_clip_enabled,
+        }
+    }
+
+    /// Run every enabled stage over interleaved samples in place
+    pub fn process(&self, samples: &mut [f32], sample_rate: u32, channels: u16) {
+        let channels = channels.max(1) as usize;
+
+        if let Some(cutoff) = self.highpass_cutoff_hz {
+            // Keep the cutoff safely below Nyquist
+            let cutoff = cutoff.min(sample_rate as f32 * 0.45);
+            let filter = Biquad::highpass(sample_rate, cutoff, std::f32::consts::FRAC_1_SQRT_2);
+            filter.apply(samples, channels);
+        }
+
+        if let Some(threshold) = self.gate_threshold_db {
+            noise_gate(samples, sample_rate, channels, threshold);
+        }
+
+        let gain_db = match self.normalize {
+            Some(NormalizeMode::Peak) => peak_db(samples).map(|peak| self.peak_target_db - peak),
+            Some(NormalizeMode::Loudness) => integrated_loudness(samples, sample_rate, channels)
+                .map(|lufs| self.loudness_target_lufs - lufs),
+            None => None,
+        };
+        if let Some(gain_db) = gain_db {
+            let gain = db_to_gain(gain_db.min(MAX_GAIN_DB));
+            log::debug!("Normalizing recording by {:.1} dB", gain_db.min(MAX_GAIN_DB));
+            samples.iter_mut().for_each(|s| *s *= gain);
+        }
+
+        if self.soft_clip {
+            samples.iter_mut().for_each(|s| *s = soft_clip(*s));
+        }
+    }
+}
+
+/// Second-order IIR filter section
+#[derive(Debug, Clone, Copy)]
+struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+impl Biquad {
+    /// Normalize by `a0`
+    fn new(b: [f32; 3], a: [f32; 3]) -> Self {
+        Self {
+            b0: b[0] / a[0],
+            b1: b[1] / a[0],
+            b2: b[2] / a[0],
+            a1: a[1] / a[0],
+            a2: a[2] / a[0],
+        }
+    }
+
+    /// High-pass filter (RBJ cookbook)
+    fn highpass(sample_rate: u32, cutoff_hz: f32, q: f32) -> Self {
+        let w0 = 2.0 * std::f32::consts::PI * cutoff_hz / sample_rate as f32;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * q);
+
+        Self::new(
+            [(1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0],
+            [1.0 + alpha, -2.0 * cos, 1.0 - alpha],
+        )
+    }
+
+    /// High-shelf filter (RBJ cookbook)
+    fn high_shelf(sample_rate: u32, freq_hz: f32, gain_db: f32, q: f32) -> Self {
+        let a = 10f32.powf(gain_db / 40.0);
+        let w0 = 2.0 * std::f32::consts::PI * freq_hz / sample_rate as f32;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * q);
+        let sqrt_a = a.sqrt();
+
+        Self::new(
+            [
+                a * ((a + 1.0) + (a - 1.0) * cos + 2.0 * sqrt_a * alpha),
+                -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+                a * ((a + 1.0) + (a - 1.0) * cos - 2.0 * sqrt_a * alpha),
+            ],
+            [
+                (a + 1.0) - (a - 1.0) * cos + 2.0 * sqrt_a * alpha,
+                2.0 * ((a - 1.0) - (a + 1.0) * cos),
+                (a + 1.0) - (a - 1.0) * cos - 2.0 * sqrt_a * alpha,
+            ],
+        )
+    }
+
+    /// Filter each channel of interleaved samples in place
+    fn apply(&self, samples: &mut [f32], channels: usize) {
+        for channel in 0..channels {
+            let (mut x1, mut x2, mut y1, mut y2) = (0.0, 0.0, 0.0, 0.0);
+            for sample in samples.iter_mut().skip(channel).step_by(channels) {
+                let x0 = *sample;
+                let y0 = self.b0 * x0 + self.b1 * x1 + self.b2 * x2 - self.a1 * y1 - self.a2 * y2;
+                (x2, x1, y2, y1) = (x1, x0, y1, y0);
+                *sample = y0;
+            }
+        }
+    }
+}
+
+/// Attenuate stretches quieter than `threshold_db`, with hold and smoothing
+fn noise_gate(samples: &mut [f32], sample_rate: u32, channels: usize, threshold_db: f32) {
+    let frame_len = ((sample_rate * GATE_FRAME_MS / 1000).max(1) as usize) * channels;
+    let hold_frames = GATE_HOLD_MS.div_ceil(GATE_FRAME_MS) as usize;
+    let floor = db_to_gain(GATE_FLOOR_DB);
+    let coefficient = |ms: f32| (-1.0 / (ms * sample_rate as f32 / 1000.0)).exp();
+    let (attack, release) = (coefficient(GATE_ATTACK_MS), coefficient(GATE_RELEASE_MS));
+
+    let mut gain = floor;
+    let mut hold = 0;
+    for frame in samples.chunks_mut(frame_len) {
+        if super::vad::frame_energy_db(frame) > threshold_db {
+            hold = hold_frames;
+        } else {
+            hold = hold.saturating_sub(1);
+        }
+        let target = if hold > 0 { 1.0 } else { floor };
+        let smoothing = if target > gain { attack } else { release };
+
+        for sample_frame in frame.chunks_mut(channels) {
+            gain = target + (gain - target) * smoothing;
+            sample_frame.iter_mut().for_each(|s| *s *= gain);
+        }
+    }
+}
+
+/// Integrated loudness in LUFS (ITU-R BS.1770), or `None` for silence
+fn integrated_loudness(samples: &[f32], sample_rate: u32, channels: usize) -> Option<f32> {
+    // K-weighting: head-related high shelf followed by a low-cut
+    let mut weighted = samples.to_vec();
+    Biquad::high_shelf(sample_rate, 1681.97, 4.0, 0.7072).apply(&mut weighted, channels);
+    Biquad::highpass(sample_rate, 38.14, 0.5003).apply(&mut weighted, channels);
+
+    let frames = weighted.len() / channels;
+    let block = ((sample_rate * LOUDNESS_BLOCK_MS / 1000) as usize).clamp(1, frames.max(1));
+    let step = ((sample_rate * LOUDNESS_STEP_MS / 1000) as usize).max(1);
+
+    let mut powers = Vec::new();
+    let mut start = 0;
+    while start + block <= frames {
+        let block_samples = &weighted[start * channels..(start + block) * channels];
+        // Mean square per channel, summed with unit channel weights
+        let power = block_samples.iter().map(|&s| (s as f64) * (s as f64)).sum::<f64>()
+            / block as f64;
+        powers.push(power);
+        start += step;
+    }
+
+    let loudness = |power: f64| -0.691 + 10.0 * power.log10();
+    let gated_mean = |threshold: f64| {
+        let gated: Vec<f64> = powers
+            .iter()
+            .copied()
+            .filter(|&p| p > 0.0 && loudness(p) > threshold)
+            .collect();
+        (!gated.is_empty()).then(|| gated.iter().sum::<f64>() / gated.len() as f64)
+    };
+
+    let absolute = gated_mean(LOUDNESS_ABSOLUTE_GATE)?;
+    let relative = gated_mean(loudness(absolute) + LOUDNESS_RELATIVE_GATE)?;
+    Some(loudness(relative) as f32)
+}
+
+/// Peak level in dBFS, or `None` for digital silence
+fn peak_db(samples: &[f32]) -> Option<f32> {
+    let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+    (peak > 0.0).then(|| 20.0 * peak.log10())
+}
+
+/// Compress peaks above the knee smoothly so output never exceeds full scale
+fn soft_clip(sample: f32) -> f32 {
+    let magnitude = sample.abs();
+    if magnitude <= SOFT_CLIP_KNEE {
+        return sample;
+    }
+    let headroom = 1.0 - SOFT_CLIP_KNEE;
+    let clipped = SOFT_CLIP_KNEE + headroom * ((magnitude - SOFT_CLIP_KNEE) / headroom).tanh();
+    clipped.copysign(sample)
+}
+
+fn db_to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16000;
+
+    fn sine(freq: f32, amplitude: f32, seconds: f32) -> Vec<f32> {
+        (0..(RATE as f32 * seconds) as usize)
+            .map(|i| (2.0 * std::f32::consts::PI * freq * i as f32 / RATE as f32).sin() * amplitude)
+            .collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    fn chain(config: AudioConfig) -> DspChain {
+        DspChain::from_config(&AudioConfig {
+            highpass_enabled: false,
+            noise_gate_enabled: false,
+            normalize_enabled: false,
+            soft_clip_enabled: false,
+            ..config
+        })
+    }
+
+    #[test]
+    fn test_highpass_removes_rumble() {
+        let dsp = DspChain {
+            highpass_cutoff_hz: Some(80.0),
+            ..chain(AudioConfig::default())
+        };
+        let mut rumble = sine(20.0, 0.5, 1.0);
+        let mut voice = sine(1000.0, 0.5, 1.0);
+
+        dsp.process(&mut rumble, RATE, 1);
+        dsp.process(&mut voice, RATE, 1);
+
+        assert!(rms(&rumble[4000..]) < 0.05);
+        assert!((rms(&voice[4000..]) - rms(&sine(1000.0, 0.5, 1.0))).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_noise_gate_attenuates_quiet_passages() {
+        let dsp = DspChain {
+            gate_threshold_db: Some(-40.0),
+            ..chain(AudioConfig::default())
+        };
+        let mut samples = [sine(300.0, 0.001, 1.0), sine(300.0, 0.5, 1.0)].concat();
+
+        dsp.process(&mut samples, RATE, 1);
+
+        assert!(rms(&samples[..RATE as usize]) < 0.0001);
+        assert!(rms(&samples[RATE as usize + 800..]) > 0.3);
+    }
+
+    #[test]
+    fn test_peak_normalization() {
+        let dsp = DspChain {
+            normalize: Some(NormalizeMode::Peak),
+            peak_target_db: -6.0,
+            ..chain(AudioConfig::default())
+        };
+        let mut samples = sine(440.0, 0.1, 0.5);
+
+        dsp.process(&mut samples, RATE, 1);
+
+        let peak = samples.iter().fold(0.0f32, |p, s| p.max(s.abs()));
+        assert!((peak - db_to_gain(-6.0)).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_loudness_normalization_reaches_target() {
+        let dsp = DspChain {
+            normalize: Some(NormalizeMode::Loudness),
+            loudness_target_lufs: -23.0,
+            ..chain(AudioConfig::default())
+        };
+        let mut samples = sine(1000.0, 0.01, 3.0);
+
+        dsp.process(&mut samples, RATE, 1);
+
+        let lufs = integrated_loudness(&samples, RATE, 1).unwrap();
+        assert!((lufs + 23.0).abs() < 0.5, "{} LUFS", lufs);
+    }
+
+    #[test]
+    fn test_loudness_of_full_scale_sine() {
+        // A 0 dBFS 1 kHz sine measures about -3 LUFS
+        let lufs = integrated_loudness(&sine(1000.0, 1.0, 2.0), RATE, 1).unwrap();
+        assert!((lufs + 3.0).abs() < 0.3, "{} LUFS", lufs);
+        assert!(integrated_loudness(&[0.0; 16000], RATE, 1).is_none());
+    }
+
+    #[test]
+    fn test_normalization_gain_is_capped() {
+        let dsp = DspChain {
+            normalize: Some(NormalizeMode::Peak),
+            peak_target_db: 0.0,
+            ..chain(AudioConfig::default())
+        };
+        let mut samples = sine(440.0, 0.0001, 0.5);
+
+        dsp.process(&mut samples, RATE, 1);
+
+        let peak = samples.iter().fold(0.0f32, |p, s| p.max(s.abs()));
+        assert!(peak < 0.0001 * db_to_gain(MAX_GAIN_DB) * 1.01);
+    }
+
+    #[test]
+    fn test_soft_clip_stays_below_full_scale() {
+        assert_eq!(soft_clip(0.5), 0.5);
+        assert!(soft_clip(1.0) < 1.0);
+        assert!(soft_clip(1.5) <= 1.0);
+        assert!(soft_clip(-3.0) >= -1.0);
+        assert!(soft_clip(0.95) > soft_clip(0.91));
+    }
+}
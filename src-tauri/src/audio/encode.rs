@@ -0,0 +1,274 @@
+use cpal::Sample;
+use flacenc::component::BitRepr;
+use flacenc::error::Verify;
+use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+
+use super::{resample, AudioData};
+use crate::config::UploadFormat;
+use crate::error::{AppError, Result};
+
+/// Sample rates the Opus encoder accepts
+const OPUS_SAMPLE_RATES: [u32; 5] = [8000, 12000, 16000, 24000, 48000];
+/// Target bitrate for Opus; plenty for speech recognition
+const OPUS_BITRATE: i32 = 32_000;
+/// Opus frame length
+const OPUS_FRAME_MS: u32 = 20;
+/// Largest packet the encoder may produce
+const OPUS_MAX_PACKET: usize = 4000;
+/// Serial number of the single logical Ogg stream
+const OGG_SERIAL: u32 = 0x4f57_4600;
+
+/// Audio encoded for upload, with the file name and MIME type to send it under
+#[derive(Debug, Clone)]
+pub struct EncodedAudio {
+    pub bytes: Vec<u8>,
+    pub file_name: &'static str,
+    pub mime_type: &'static str,
+}
+
+/// Encode audio in the given upload format
+pub fn encode(audio: &AudioData, format: UploadFormat) -> Result<EncodedAudio> {
+    let encoded = match format {
+        UploadFormat::Wav => EncodedAudio {
+            bytes: audio.to_wav_bytes()?,
+            file_name: "recording.wav",
+            mime_type: "audio/wav",
+        },
+        UploadFormat::Flac => EncodedAudio {
+            bytes: encode_flac(audio)?,
+            file_name: "recording.flac",
+            mime_type: "audio/flac",
+        },
+        UploadFormat::Opus => EncodedAudio {
+            bytes: encode_opus(audio)?,
+            file_name: "recording.ogg",
+            mime_type: "audio/ogg",
+        },
+    };
+
+    log::debug!(
+        "Encoded {:.2}s of audio as {:?}: {} bytes",
+        audio.duration_seconds(),
+        format,
+        encoded.bytes.len()
+    );
+    Ok(encoded)
+}
+
+/// Encode as 16-bit FLAC
+pub fn encode_flac(audio: &AudioData) -> Result<Vec<u8>> {
+    let samples: Vec<i32> = audio.samples.iter().map(|&s| to_i16(s) as i32).collect();
+
+    let config = flacenc::config::Encoder::default()
+        .into_verified()
+        .map_err(|(_, e)| AppError::AudioRecording(format!("Invalid FLAC config: {:?}", e)))?;
+    let source = flacenc::source::MemSource::from_samples(
+        &samples,
+        audio.channels as usize,
+        16,
+        audio.sample_rate as usize,
+    );
+    let stream = flacenc::encode_with_fixed_block_size(&config, source, config.block_size)
+        .map_err(|e| AppError::AudioRecording(format!("Failed to encode FLAC: {:?}", e)))?;
+
+    let mut sink = flacenc::bitsink::ByteSink::new();
+    stream
+        .write(&mut sink)
+        .map_err(|e| AppError::AudioRecording(format!("Failed to write FLAC: {:?}", e)))?;
+
+    Ok(sink.as_slice().to_vec())
+}
+
+/// Encode as Opus in an Ogg container
+///
+/// Audio at a rate Opus does not support is resampled to the next supported
+/// rate, and more than two channels are mixed down to stereo.
+pub fn encode_opus(audio: &AudioData) -> Result<Vec<u8>> {
+    let rate = OPUS_SAMPLE_RATES
+        .into_iter()
+        .find(|&r| r >= audio.sample_rate)
+        .unwrap_or(48000);
+    let channels = audio.channels.clamp(1, 2);
+    let samples = resample::convert(
+        &audio.samples,
+        audio.channels,
+        audio.sample_rate,
+        channels,
+        rate,
+    );
+
+    let opus_err = |e: opus::Error| AppError::AudioRecording(format!("Opus encoder error: {}", e));
+    let mut encoder = opus::Encoder::new(
+        rate,
+        if channels == 1 {
+            opus::Channels::Mono
+        } else {
+            opus::Channels::Stereo
+        },
+        opus::Application::Voip,
+    )
+    .map_err(opus_err)?;
+    encoder
+        .set_bitrate(opus::Bitrate::Bits(OPUS_BITRATE))
+        .map_err(opus_err)?;
+
+    // Granule positions are always counted at 48 kHz
+    let to_48k = |frames: u64| frames * 48000 / rate as u64;
+    let lookahead = encoder.get_lookahead().map_err(opus_err)? as u64;
+    let pre_skip = to_48k(lookahead);
+    let frame_len = (rate * OPUS_FRAME_MS / 1000) as usize;
+    let total_frames = (samples.len() / channels as usize) as u64;
+    let end_granule = pre_skip + to_48k(total_frames);
+
+    let mut writer = PacketWriter::new(Vec::new());
+    let write_err =
+        |e: std::io::Error| AppError::AudioRecording(format!("Failed to write Ogg: {}", e));
+    writer
+        .write_packet(
+            opus_head(channels, pre_skip as u16, audio.sample_rate),
+            OGG_SERIAL,
+            PacketWriteEndInfo::EndPage,
+            0,
+        )
+        .map_err(write_err)?;
+    writer
+        .write_packet(opus_tags(), OGG_SERIAL, PacketWriteEndInfo::EndPage, 0)
+        .map_err(write_err)?;
+
+    // The encoder holds back `lookahead` frames, so silence is fed after the
+    // audio until all of it has come out the other end
+    let packets = (total_frames + lookahead).div_ceil(frame_len as u64).max(1) as usize;
+    let mut chunks = samples.chunks(frame_len * channels as usize);
+    let mut frame = vec![0.0; frame_len * channels as usize];
+    let mut packet = vec![0u8; OPUS_MAX_PACKET];
+    let mut encoded_frames = 0u64;
+
+    for index in 0..packets {
+        let chunk = chunks.next().unwrap_or_default();
+        frame[..chunk.len()].copy_from_slice(chunk);
+        frame[chunk.len()..].fill(0.0);
+
+        let len = encoder
+            .encode_float(&frame, &mut packet)
+            .map_err(opus_err)?;
+        encoded_frames += frame_len as u64;
+
+        let end_info = if index + 1 == packets {
+            PacketWriteEndInfo::EndStream
+        } else {
+            PacketWriteEndInfo::NormalPacket
+        };
+        writer
+            .write_packet(
+                packet[..len].to_vec(),
+                OGG_SERIAL,
+                end_info,
+                to_48k(encoded_frames).min(end_granule),
+            )
+            .map_err(write_err)?;
+    }
+
+    Ok(writer.into_inner())
+}
+
+/// Opus identification header (RFC 7845, section 5.1)
+fn opus_head(channels: u16, pre_skip: u16, input_sample_rate: u32) -> Vec<u8> {
+    let mut head = Vec::with_capacity(19);
+    head.extend_from_slice(b"OpusHead");
+    head.push(1); // Version
+    head.push(channels as u8);
+    head.extend_from_slice(&pre_skip.to_le_bytes());
+    head.extend_from_slice(&input_sample_rate.to_le_bytes());
+    head.extend_from_slice(&0i16.to_le_bytes()); // Output gain
+    head.push(0); // Channel mapping family
+    head
+}
+
+/// Opus comment header with no user comments (RFC 7845, section 5.2)
+fn opus_tags() -> Vec<u8> {
+    let vendor = b"open-whisperflow";
+    let mut tags = Vec::with_capacity(16 + vendor.len());
+    tags.extend_from_slice(b"OpusTags");
+    tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    tags.extend_from_slice(vendor);
+    tags.extend_from_slice(&0u32.to_le_bytes());
+    tags
+}
+
+/// Convert f32 [-1.0, 1.0] to i16, saturating samples outside the range
+///
+/// The inverse of the input conversion, so -1.0 maps to `i16::MIN`.
+pub(super) fn to_i16(sample: f32) -> i16 {
+    sample.clamp(-1.0, 1.0).to_sample::<i16>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(rate: u32, seconds: f32) -> Vec<f32> {
+        (0..(rate as f32 * seconds) as usize)
+            .map(|i| (2.0 * std::f32::consts::PI * 440.0 * i as f32 / rate as f32).sin() * 0.5)
+            .collect()
+    }
+
+    #[test]
+    fn test_flac_is_smaller_than_wav() {
+        let audio = AudioData::new(tone(16000, 2.0), 16000, 1);
+
+        let flac = encode(&audio, UploadFormat::Flac).unwrap();
+        let wav = encode(&audio, UploadFormat::Wav).unwrap();
+
+        assert_eq!(&flac.bytes[..4], b"fLaC");
+        assert_eq!(flac.mime_type, "audio/flac");
+        assert!(flac.bytes.len() < wav.bytes.len());
+    }
+
+    #[test]
+    fn test_opus_round_trip() {
+        let audio = AudioData::new(tone(16000, 1.0), 16000, 1);
+
+        let encoded = encode(&audio, UploadFormat::Opus).unwrap();
+        assert!(encoded.bytes.len() < audio.to_wav_bytes().unwrap().len() / 4);
+
+        let mut reader = ogg::reading::PacketReader::new(std::io::Cursor::new(encoded.bytes));
+        let head = reader.read_packet_expected().unwrap();
+        assert_eq!(&head.data[..8], b"OpusHead");
+        assert_eq!(head.data[9], 1);
+        let tags = reader.read_packet_expected().unwrap();
+        assert_eq!(&tags.data[..8], b"OpusTags");
+
+        let mut decoder = opus::Decoder::new(16000, opus::Channels::Mono).unwrap();
+        let mut output = vec![0.0; 1920];
+        let mut decoded = 0;
+        let mut last_granule = 0;
+        while let Some(packet) = reader.read_packet().unwrap() {
+            decoded += decoder
+                .decode_float(&packet.data, &mut output, false)
+                .unwrap();
+            last_granule = packet.absgp_page();
+        }
+
+        // The decoder delay is flushed, so every input sample is decoded
+        // after the pre-skip and the final granule trims only the padding
+        let pre_skip = u16::from_le_bytes([head.data[10], head.data[11]]) as u64;
+        assert_eq!(last_granule, pre_skip + 48000);
+        assert!(decoded as u64 * 3 >= last_granule);
+        assert!((decoded as u64 * 3) < last_granule + 960);
+    }
+
+    #[test]
+    fn test_opus_resamples_unsupported_rates() {
+        let audio = AudioData::new(tone(44100, 0.5), 44100, 2);
+        let encoded = encode_opus(&audio).unwrap();
+
+        let mut reader = ogg::reading::PacketReader::new(std::io::Cursor::new(encoded));
+        let head = reader.read_packet_expected().unwrap();
+        assert_eq!(head.data[9], 2);
+        // The original rate is recorded for information only
+        assert_eq!(
+            u32::from_le_bytes(head.data[12..16].try_into().unwrap()),
+            44100
+        );
+    }
+}
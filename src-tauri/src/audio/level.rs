@@ -0,0 +1,136 @@
+use serde::Serialize;
+
+/// How often a level reading is produced while recording
+pub const LEVEL_INTERVAL_MS: u32 = 50;
+/// Number of points in the waveform sent with each reading
+pub const WAVEFORM_POINTS: usize = 32;
+
+/// Input level over one metering interval
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AudioLevel {
+    /// RMS level, 0.0-1.0
+    pub rms: f32,
+    /// Peak absolute sample, 0.0-1.0
+    pub peak: f32,
+    /// Peak of each slice of the interval, for drawing a waveform
+    pub waveform: Vec<f32>,
+}
+
+/// Accumulates live samples and yields a reading every metering interval
+#[derive(Debug, Clone)]
+pub struct LevelMeter {
+    channels: usize,
+    frames_per_reading: usize,
+    frames_per_point: usize,
+    frames: usize,
+    sum_squares: f64,
+    peak: f32,
+    point_peak: f32,
+    waveform: Vec<f32>,
+}
+
+impl LevelMeter {
+    pub fn new(sample_rate: u32, channels: u16) -> Self {
+        let frames_per_reading =
+            ((sample_rate as u64 * LEVEL_INTERVAL_MS as u64) / 1000).max(1) as usize;
+
+        Self {
+            channels: channels.max(1) as usize,
+            frames_per_reading,
+            frames_per_point: frames_per_reading.div_ceil(WAVEFORM_POINTS),
+            frames: 0,
+            sum_squares: 0.0,
+            peak: 0.0,
+            point_peak: 0.0,
+            waveform: Vec::with_capacity(WAVEFORM_POINTS),
+        }
+    }
+
+    /// Feed interleaved samples; returns the most recent completed reading, if any
+    pub fn push(&mut self, samples: &[f32]) -> Option<AudioLevel> {
+        let mut reading = None;
+
+        for frame in samples.chunks(self.channels) {
+            let value = frame.iter().sum::<f32>() / frame.len() as f32;
+            let magnitude = value.abs().min(1.0);
+
+            self.sum_squares += (value as f64) * (value as f64);
+            self.peak = self.peak.max(magnitude);
+            self.point_peak = self.point_peak.max(magnitude);
+            self.frames += 1;
+
+            if self.frames.is_multiple_of(self.frames_per_point) {
+                self.waveform.push(self.point_peak);
+                self.point_peak = 0.0;
+            }
+
+            if self.frames == self.frames_per_reading {
+                reading = Some(self.take_reading());
+            }
+        }
+
+        reading
+    }
+
+    fn take_reading(&mut self) -> AudioLevel {
+        if !self.frames.is_multiple_of(self.frames_per_point) {
+            self.waveform.push(self.point_peak);
+        }
+
+        let reading = AudioLevel {
+            rms: ((self.sum_squares / self.frames as f64).sqrt() as f32).min(1.0),
+            peak: self.peak,
+            waveform: std::mem::replace(&mut self.waveform, Vec::with_capacity(WAVEFORM_POINTS)),
+        };
+
+        self.frames = 0;
+        self.sum_squares = 0.0;
+        self.peak = 0.0;
+        self.point_peak = 0.0;
+        reading
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reading_every_interval() {
+        // 50 ms at 16 kHz is 800 frames
+        let mut meter = LevelMeter::new(16000, 1);
+
+        assert!(meter.push(&[0.5; 799]).is_none());
+        let reading = meter.push(&[0.5; 1]).unwrap();
+
+        assert!((reading.rms - 0.5).abs() < 1e-6);
+        assert!((reading.peak - 0.5).abs() < 1e-6);
+        assert_eq!(reading.waveform.len(), WAVEFORM_POINTS);
+    }
+
+    #[test]
+    fn test_peak_and_waveform_follow_signal() {
+        let mut meter = LevelMeter::new(16000, 2);
+        let mut samples = vec![0.0; 1600];
+        // A loud stereo frame in the second half of the interval
+        samples[1200] = -0.9;
+        samples[1201] = -0.9;
+
+        let reading = meter.push(&samples).unwrap();
+
+        assert!((reading.peak - 0.9).abs() < 1e-6);
+        assert!(reading.waveform[..WAVEFORM_POINTS / 2].iter().all(|&p| p == 0.0));
+        assert!(reading.waveform[WAVEFORM_POINTS / 2..].iter().any(|&p| p > 0.8));
+    }
+
+    #[test]
+    fn test_meter_resets_between_readings() {
+        let mut meter = LevelMeter::new(16000, 1);
+
+        meter.push(&[1.0; 800]).unwrap();
+        let quiet = meter.push(&[0.0; 800]).unwrap();
+
+        assert_eq!(quiet.rms, 0.0);
+        assert_eq!(quiet.peak, 0.0);
+    }
+}
@@ -1,222 +1,670 @@
-use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use cpal::{Device, SampleFormat, Stream, StreamConfig};
-use hound::{WavSpec, WavWriter};
-use std::path::{Path, PathBuf};
+mod actor;
+pub mod chunk;
+mod data;
+pub mod decode;
+pub mod dsp;
+pub mod encode;
+pub mod level;
+pub mod pre_roll;
+pub mod resample;
+pub mod source;
+pub mod spool;
+pub mod vad;
+
+pub use actor::RecorderHandle;
+pub use data::AudioData;
+
+use cpal::traits::{DeviceTrait, HostTrait};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
 
+use crate::config::{AudioConfig, CaptureMode};
 use crate::error::{AppError, Result};
+use dsp::DspChain;
+use level::{AudioLevel, LevelMeter};
+use pre_roll::PreRollBuffer;
+use source::{AudioSource, InputStream};
+use vad::{SilenceMonitor, VoiceActivityDetector};
+
+/// Device id that selects the host's default input device
+pub const DEFAULT_DEVICE_ID: &str = "default";
+
+/// An input device and the stream configurations it supports
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioDeviceInfo {
+    pub name: String,
+    pub is_default: bool,
+    /// Monitor source that captures system playback
+    pub is_monitor: bool,
+    pub supported_configs: Vec<SupportedConfigInfo>,
+}
+
+/// A supported input stream configuration range
+#[derive(Debug, Clone, Serialize)]
+pub struct SupportedConfigInfo {
+    pub channels: u16,
+    pub min_sample_rate: u32,
+    pub max_sample_rate: u32,
+    pub sample_format: String,
+}
+
+/// List every input device exposed by the default host
+pub fn list_input_devices() -> Result<Vec<AudioDeviceInfo>> {
+    let host = cpal::default_host();
+    let default_name = host
+        .default_input_device()
+        .and_then(|device| device.name().ok());
+
+    let devices = host
+        .input_devices()
+        .map_err(|e| AppError::AudioRecording(format!("Failed to enumerate devices: {}", e)))?;
+
+    let mut result = Vec::new();
+    for device in devices {
+        let name = match device.name() {
+            Ok(name) => name,
+            Err(e) => {
+                log::warn!("Skipping input device without a name: {}", e);
+                continue;
+            }
+        };
+
+        let supported_configs = match device.supported_input_configs() {
+            Ok(configs) => configs
+                .map(|c| SupportedConfigInfo {
+                    channels: c.channels(),
+                    min_sample_rate: c.min_sample_rate().0,
+                    max_sample_rate: c.max_sample_rate().0,
+                    sample_format: c.sample_format().to_string(),
+                })
+                .collect(),
+            Err(e) => {
+                log::warn!("Failed to query configs for {}: {}", name, e);
+                Vec::new()
+            }
+        };
+
+        result.push(AudioDeviceInfo {
+            is_default: default_name.as_deref() == Some(name.as_str()),
+            is_monitor: source::is_monitor_name(&name),
+            name,
+            supported_configs,
+        });
+    }
+
+    Ok(result)
+}
+
+/// Save a recording as a WAV file in the archive directory
+///
+/// An empty `archive_dir` uses the app's data directory.
+pub fn archive_recording(audio: &AudioData, archive_dir: &str) -> Result<PathBuf> {
+    let dir = if archive_dir.is_empty() {
+        dirs::data_dir()
+            .ok_or_else(|| AppError::NotFound("No data directory available".to_string()))?
+            .join("open-whisperflow")
+            .join("recordings")
+    } else {
+        PathBuf::from(archive_dir)
+    };
+    std::fs::create_dir_all(&dir)?;
+
+    let path = dir.join(format!(
+        "recording_{}.wav",
+        chrono::Local::now().format("%Y%m%d_%H%M%S_%3f")
+    ));
+    audio.save_wav(&path)?;
+
+    Ok(path)
+}
+
+/// Convert audio to the configured output format, trim silence and run the DSP chain
+///
+/// Used for both microphone recordings and decoded files. Audio without any
+/// speech comes back empty.
+pub fn prepare_audio(audio: &AudioData, config: &AudioConfig) -> Result<AudioData> {
+    // Downmix and resample to the configured output format
+    let mut samples = resample::convert(
+        &audio.samples,
+        audio.channels,
+        audio.sample_rate,
+        config.channels,
+        config.sample_rate,
+    );
+
+    if config.vad_enabled {
+        let vad = VoiceActivityDetector::from_config(config, config.sample_rate);
+        samples = trim_silence(&vad, &samples, config);
+        if samples.is_empty() {
+            log::info!("No speech detected");
+            return Ok(AudioData::new(samples, config.sample_rate, config.channels));
+        }
+    }
+
+    DspChain::from_config(config).process(&mut samples, config.sample_rate, config.channels);
+
+    Ok(AudioData::new(samples, config.sample_rate, config.channels))
+}
+
+/// Remove silence with the VAD, analysing a mono mix of the output format
+fn trim_silence(vad: &VoiceActivityDetector, samples: &[f32], config: &AudioConfig) -> Vec<f32> {
+    let channels = config.channels.max(1) as usize;
+    let before = samples.len();
+
+    let trimmed = if channels == 1 {
+        vad.process(samples)
+    } else {
+        let mono = resample::remix_channels(samples, config.channels, 1);
+        let keep = vad.keep_mask(&mono);
+        samples
+            .chunks_exact(channels)
+            .zip(keep)
+            .filter_map(|(frame, keep)| keep.then_some(frame))
+            .flatten()
+            .copied()
+            .collect()
+    };
+
+    log::info!(
+        "VAD kept {:.2}s of {:.2}s",
+        trimmed.len() as f32 / (config.sample_rate as f32 * channels as f32),
+        before as f32 / (config.sample_rate as f32 * channels as f32)
+    );
+    trimmed
+}
+
+/// Why the recorder ended a recording on its own
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum AutoStopReason {
+    /// Speech was followed by the configured amount of silence
+    Silence,
+    /// The recording reached `max_recording_duration_seconds`
+    TimeLimit,
+}
+
+/// Events raised by the recorder while a recording is running
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecorderEvent {
+    /// The recording should be stopped and processed
+    AutoStop(AutoStopReason),
+    /// The recording will hit its time limit soon
+    TimeLimitApproaching {
+        remaining_seconds: u64,
+        max_seconds: u64,
+    },
+    /// Periodic input level reading for live metering
+    Level(AudioLevel),
+    /// The input stream failed, e.g. because the device was unplugged
+    StreamFailed(String),
+    /// A finite input such as a file has delivered all of its audio
+    ///
+    /// The stream stays quiet from here on without having failed.
+    InputEnded,
+}
+
+/// Receiving end of the events for one recording
+///
+/// The channel closes when the recording ends.
+pub type RecorderEvents = mpsc::UnboundedReceiver<RecorderEvent>;
+
+/// Maximum length of a recording, counted in interleaved samples
+#[derive(Debug, Clone)]
+struct DurationLimit {
+    max_seconds: u64,
+    max_samples: usize,
+    warning_seconds: u64,
+    warn_at_samples: Option<usize>,
+    warned: bool,
+    reached: bool,
+}
+
+impl DurationLimit {
+    /// Build the limit for a stream, or `None` if recordings are unlimited
+    fn new(config: &AudioConfig, sample_rate: u32, channels: u16) -> Option<Self> {
+        if config.max_recording_duration_seconds == 0 {
+            return None;
+        }
+
+        let samples_per_second = sample_rate as usize * channels.max(1) as usize;
+        let max_seconds = config.max_recording_duration_seconds;
+        let warning_seconds = config.duration_warning_seconds;
+        let warn_at_samples = (warning_seconds > 0 && warning_seconds < max_seconds)
+            .then(|| (max_seconds - warning_seconds) as usize * samples_per_second);
+
+        Some(Self {
+            max_seconds,
+            max_samples: max_seconds as usize * samples_per_second,
+            warning_seconds,
+            warn_at_samples,
+            warned: false,
+            reached: false,
+        })
+    }
+
+    /// How many more samples may be recorded after `recorded`
+    fn remaining(&self, recorded: usize) -> usize {
+        self.max_samples.saturating_sub(recorded)
+    }
+
+    /// Event to raise once `recorded` samples have been captured, if any
+    fn check(&mut self, recorded: usize) -> Option<RecorderEvent> {
+        if !self.reached && recorded >= self.max_samples {
+            self.reached = true;
+            return Some(RecorderEvent::AutoStop(AutoStopReason::TimeLimit));
+        }
+
+        match self.warn_at_samples {
+            Some(warn_at) if !self.warned && recorded >= warn_at => {
+                self.warned = true;
+                Some(RecorderEvent::TimeLimitApproaching {
+                    remaining_seconds: self.warning_seconds,
+                    max_seconds: self.max_seconds,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Live monitors and event channel of the recording in progress
+struct Session {
+    /// Input is dropped while paused; monitors resume where they left off
+    paused: bool,
+    silence: Option<SilenceMonitor>,
+    limit: Option<DurationLimit>,
+    level: LevelMeter,
+    events: mpsc::UnboundedSender<RecorderEvent>,
+}
+
+/// Error callback for an input stream
+///
+/// Only the first error of a stream is reported to the recording; the
+/// backend tends to repeat itself once a device is gone.
+fn stream_error_handler(
+    session: Arc<Mutex<Option<Session>>>,
+    failed: Arc<AtomicBool>,
+) -> impl FnMut(String) + Send + 'static {
+    move |message| {
+        log::error!("Audio stream error: {}", message);
+        if failed.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        if let Some(session) = session.lock().unwrap().as_ref() {
+            session.events.send(RecorderEvent::StreamFailed(message)).ok();
+        }
+    }
+}
+
+/// State owned by the input stream callback
+struct Capture {
+    buffer: Arc<Mutex<Vec<f32>>>,
+    /// Set while a recording is running
+    session: Arc<Mutex<Option<Session>>>,
+    /// Filled between recordings when the stream is kept warm
+    pre_roll: Option<PreRollBuffer>,
+}
+
+impl Capture {
+    /// Append converted samples from the device and run live monitors
+    fn push(&mut self, samples: &[f32]) {
+        let mut session = self.session.lock().unwrap();
+        let Some(session) = session.as_mut() else {
+            if let Some(pre_roll) = &mut self.pre_roll {
+                pre_roll.push(samples);
+            }
+            return;
+        };
+        // Paused input is dropped; it must not resurface through the pre-roll
+        if session.paused {
+            return;
+        }
+
+        let recorded = {
+            let mut buffer = self.buffer.lock().unwrap();
+            // A new recording starts with the audio from just before it
+            if let Some(pre_roll) = &mut self.pre_roll {
+                pre_roll.drain_into(&mut buffer);
+            }
+
+            // Never grow past the time limit, even before the stop is processed
+            let samples = match &session.limit {
+                Some(limit) => &samples[..samples.len().min(limit.remaining(buffer.len()))],
+                None => samples,
+            };
+            buffer.extend_from_slice(samples);
+            buffer.len()
+        };
+
+        if let Some(level) = session.level.push(samples) {
+            session.events.send(RecorderEvent::Level(level)).ok();
+        }
+
+        if let Some(event) = session.limit.as_mut().and_then(|limit| limit.check(recorded)) {
+            log::info!("Recording time limit event: {:?}", event);
+            session.events.send(event).ok();
+        }
+
+        if let Some(monitor) = &mut session.silence {
+            if monitor.push(samples) {
+                log::info!("Trailing silence detected, requesting auto-stop");
+                session.silence = None;
+                session
+                    .events
+                    .send(RecorderEvent::AutoStop(AutoStopReason::Silence))
+                    .ok();
+            }
+        }
+    }
+}
 
 pub struct AudioRecorder {
-    device: Device,
-    config: StreamConfig,
-    sample_format: SampleFormat,
-    stream: Option<Stream>,
+    /// Input settings the source was opened with
+    device_id: String,
+    capture_mode: CaptureMode,
+    monitor_device_id: String,
+    source: Box<dyn AudioSource>,
+    /// Audio settings for the next recording
+    settings: AudioConfig,
+    stream: Option<Box<dyn InputStream>>,
     buffer: Arc<Mutex<Vec<f32>>>,
-    is_recording: Arc<Mutex<bool>>,
+    session: Arc<Mutex<Option<Session>>>,
+    /// Set by the stream's error callback; the stream is unusable afterwards
+    stream_failed: Arc<AtomicBool>,
 }
 
 impl AudioRecorder {
-    /// Create a new audio recorder with default input device
-    pub fn new() -> Result<Self> {
-        let host = cpal::default_host();
-        let device = host
-            .default_input_device()
-            .ok_or_else(|| AppError::AudioRecording("No input device available".to_string()))?;
-
-        log::info!("Using audio device: {}", device.name().unwrap_or_default());
-
-        let supported_config = device
-            .default_input_config()
-            .map_err(|e| AppError::AudioRecording(format!("Failed to get default config: {}", e)))?;
-
-        let sample_format = supported_config.sample_format();
-        let config = supported_config.into();
-
-        Ok(Self {
-            device,
-            config,
-            sample_format,
+    /// Create a new audio recorder on the inputs selected in the config
+    pub fn new(config: &AudioConfig) -> Result<Self> {
+        let source = source::open_source(config)?;
+        Ok(Self::with_source(source, config))
+    }
+
+    /// Create a recorder that takes its input from `source`
+    pub fn with_source(source: Box<dyn AudioSource>, config: &AudioConfig) -> Self {
+        let mut recorder = Self {
+            device_id: config.device_id.clone(),
+            capture_mode: config.capture_mode,
+            monitor_device_id: config.monitor_device_id.clone(),
+            source,
+            settings: config.clone(),
             stream: None,
             buffer: Arc::new(Mutex::new(Vec::new())),
-            is_recording: Arc::new(Mutex::new(false)),
-        })
+            session: Arc::new(Mutex::new(None)),
+            stream_failed: Arc::new(AtomicBool::new(false)),
+        };
+        recorder.apply_config(config);
+
+        recorder
+    }
+
+    /// Apply output format, VAD, DSP, auto-stop, limit and pre-roll settings for the next recording
+    ///
+    /// The input device is not reopened; create a new recorder for that.
+    /// With pre-roll enabled the input stream is opened here and kept warm
+    /// between recordings.
+    pub fn apply_config(&mut self, config: &AudioConfig) {
+        let pre_roll_changed = self.settings.pre_roll_enabled != config.pre_roll_enabled
+            || self.settings.pre_roll_ms != config.pre_roll_ms;
+
+        self.settings = config.clone();
+
+        if self.is_recording() {
+            return;
+        }
+        if pre_roll_changed {
+            self.stream = None;
+        }
+        if self.keeps_stream_warm() && self.stream.is_none() {
+            match self.open_stream() {
+                Ok(()) => log::info!(
+                    "Input stream kept warm with {} ms pre-roll",
+                    config.pre_roll_ms
+                ),
+                Err(e) => log::warn!("Failed to open input stream for pre-roll: {}", e),
+            }
+        }
+    }
+
+    /// Whether the recorder was opened with the input settings in `config`
+    ///
+    /// Other settings can be applied with [`apply_config`](Self::apply_config).
+    pub fn uses_input_of(&self, config: &AudioConfig) -> bool {
+        self.device_id == config.device_id
+            && self.capture_mode == config.capture_mode
+            && self.monitor_device_id == config.monitor_device_id
+    }
+
+    /// Whether the input stream stays open between recordings to fill the pre-roll
+    ///
+    /// Finite inputs are only opened for a recording; warming one up would
+    /// play the whole clip into the pre-roll.
+    fn keeps_stream_warm(&self) -> bool {
+        self.settings.pre_roll_enabled && !self.source.is_finite()
+    }
+
+    /// Whether a recording is in progress
+    pub fn is_recording(&self) -> bool {
+        self.session.lock().unwrap().is_some()
     }
 
     /// Start recording audio
-    pub fn start_recording(&mut self) -> Result<()> {
-        *self.is_recording.lock().unwrap() = true;
+    ///
+    /// Returns the channel on which auto-stop and other recorder events arrive.
+    pub fn start_recording(&mut self) -> Result<RecorderEvents> {
         self.buffer.lock().unwrap().clear();
 
-        let buffer = Arc::clone(&self.buffer);
-        let is_recording = Arc::clone(&self.is_recording);
+        // A warm stream that failed between recordings is reopened, on the
+        // default device if the configured one is gone
+        if self.stream_failed.load(Ordering::SeqCst) {
+            self.stream = None;
+            self.switch_device()?;
+        }
 
-        let err_fn = |err| {
-            log::error!("Audio stream error: {}", err);
-        };
+        let (events, receiver) = mpsc::unbounded_channel();
+        *self.session.lock().unwrap() = Some(self.new_session(events));
 
-        let stream = match self.sample_format {
-            SampleFormat::F32 => self.build_stream_f32(buffer, is_recording, err_fn)?,
-            SampleFormat::I16 => self.build_stream_i16(buffer, is_recording, err_fn)?,
-            SampleFormat::U16 => self.build_stream_u16(buffer, is_recording, err_fn)?,
-            _ => {
-                return Err(AppError::AudioRecording(
-                    "Unsupported sample format".to_string(),
-                ))
+        if self.stream.is_none() {
+            if let Err(e) = self.open_stream() {
+                *self.session.lock().unwrap() = None;
+                return Err(e);
             }
-        };
-
-        stream
-            .play()
-            .map_err(|e| AppError::AudioRecording(format!("Failed to play stream: {}", e)))?;
+        }
 
-        self.stream = Some(stream);
         log::info!("Started recording");
+        Ok(receiver)
+    }
 
+    /// Stop appending input to the recording without closing the stream
+    pub fn pause_recording(&mut self) -> Result<()> {
+        self.set_paused(true)?;
+        log::info!("Recording paused");
         Ok(())
     }
 
-    /// Stop recording and save to WAV file
-    pub fn stop_recording(&mut self) -> Result<PathBuf> {
-        *self.is_recording.lock().unwrap() = false;
+    /// Continue appending input to a paused recording
+    pub fn resume_recording(&mut self) -> Result<()> {
+        self.set_paused(false)?;
+        log::info!("Recording resumed");
+        Ok(())
+    }
 
-        if let Some(stream) = self.stream.take() {
-            drop(stream);
+    /// Reopen the input after a stream error and carry on with the recording
+    ///
+    /// Uses the configured device if it is still present and the default
+    /// device otherwise. Audio captured so far is kept. Returns the name of
+    /// the device now in use.
+    pub fn reconnect(&mut self) -> Result<String> {
+        self.stream = None;
+        self.switch_device()?;
+        self.open_stream()?;
+
+        let name = self.source.name();
+        log::info!("Recording continues on {}", name);
+        Ok(name)
+    }
+
+    /// End the recording after an unrecoverable stream error
+    ///
+    /// Returns the audio captured so far in the target format, like
+    /// [`stop_recording`](Self::stop_recording), if any speech is left.
+    pub fn abort_recording(&mut self) -> Result<Option<AudioData>> {
+        self.end_session();
+        self.stream = None;
+
+        let audio = self.take_prepared()?.filter(|audio| !audio.is_empty());
+        log::info!("Recording aborted");
+        Ok(audio)
+    }
+
+    /// Select the input again and adapt the recording in progress to it
+    fn switch_device(&mut self) -> Result<()> {
+        let before = self.source.format();
+        self.source.reselect()?;
+        let after = self.source.format();
+
+        // Earlier audio is converted so the buffer stays in one format
+        if after != before {
+            let mut buffer = self.buffer.lock().unwrap();
+            if !buffer.is_empty() {
+                *buffer = resample::convert(
+                    &buffer,
+                    before.channels,
+                    before.sample_rate,
+                    after.channels,
+                    after.sample_rate,
+                );
+            }
         }
 
-        let buffer = self.buffer.lock().unwrap();
+        // Monitors are rebuilt for the new format, keeping the event channel
+        let mut session = self.session.lock().unwrap();
+        if let Some(current) = session.take() {
+            let mut replacement = self.new_session(current.events);
+            replacement.paused = current.paused;
+            *session = Some(replacement);
+        }
+        Ok(())
+    }
 
-        if buffer.is_empty() {
-            return Err(AppError::AudioRecording(
-                "No audio data recorded".to_string(),
-            ));
+    /// Live monitors for a recording in the current stream format
+    fn new_session(&self, events: mpsc::UnboundedSender<RecorderEvent>) -> Session {
+        let format = self.source.format();
+        Session {
+            paused: false,
+            silence: self.settings.auto_stop_enabled.then(|| {
+                SilenceMonitor::new(format.sample_rate, format.channels, &self.settings)
+            }),
+            limit: DurationLimit::new(&self.settings, format.sample_rate, format.channels),
+            level: LevelMeter::new(format.sample_rate, format.channels),
+            events,
         }
+    }
 
-        // Save to temporary file
-        let temp_path = std::env::temp_dir().join(format!(
-            "openwhisperflow_{}.wav",
-            chrono::Utc::now().timestamp()
-        ));
+    fn set_paused(&mut self, paused: bool) -> Result<()> {
+        self.session
+            .lock()
+            .unwrap()
+            .as_mut()
+            .ok_or_else(|| AppError::InvalidState("Not recording".to_string()))?
+            .paused = paused;
+        Ok(())
+    }
 
-        self.save_wav(&temp_path, &buffer)?;
+    /// Open and start the input stream
+    fn open_stream(&mut self) -> Result<()> {
+        let format = self.source.format();
+        let mut capture = Capture {
+            buffer: Arc::clone(&self.buffer),
+            session: Arc::clone(&self.session),
+            pre_roll: self.keeps_stream_warm().then(|| {
+                PreRollBuffer::new(format.sample_rate, format.channels, self.settings.pre_roll_ms)
+            }),
+        };
 
-        log::info!("Stopped recording, saved to: {}", temp_path.display());
-        log::info!(
-            "Recorded {} samples ({:.2} seconds)",
-            buffer.len(),
-            buffer.len() as f32 / self.config.sample_rate.0 as f32
-        );
+        self.stream_failed.store(false, Ordering::SeqCst);
+        let on_error =
+            stream_error_handler(Arc::clone(&self.session), Arc::clone(&self.stream_failed));
+
+        let stream = self.source.open(
+            Box::new(move |samples| capture.push(samples)),
+            Box::new(on_error),
+        )?;
+
+        if self.source.is_finite() {
+            if let Some(session) = self.session.lock().unwrap().as_ref() {
+                session.events.send(RecorderEvent::InputEnded).ok();
+            }
+        }
 
-        Ok(temp_path)
+        self.stream = Some(stream);
+        Ok(())
     }
 
-    /// Save audio buffer to WAV file
-    fn save_wav(&self, path: &Path, samples: &[f32]) -> Result<()> {
-        let spec = WavSpec {
-            channels: self.config.channels,
-            sample_rate: self.config.sample_rate.0,
-            bits_per_sample: 16,
-            sample_format: hound::SampleFormat::Int,
+    /// End the current recording, closing the stream unless it is kept warm
+    fn end_session(&mut self) {
+        self.session.lock().unwrap().take();
+
+        if !self.keeps_stream_warm() {
+            self.stream = None;
+        }
+    }
+
+    /// Stop recording and return the audio in the target format
+    ///
+    /// The audio is empty if nothing was captured or none of it was speech.
+    pub fn stop_recording(&mut self) -> Result<AudioData> {
+        self.end_session();
+
+        let audio = match self.take_prepared()? {
+            Some(audio) => audio,
+            None => {
+                log::info!("No audio data recorded");
+                AudioData::new(Vec::new(), self.settings.sample_rate, self.settings.channels)
+            }
         };
+        log::info!("Stopped recording ({:.2} seconds)", audio.duration_seconds());
 
-        let mut writer = WavWriter::create(path, spec)
-            .map_err(|e| AppError::AudioRecording(format!("Failed to create WAV file: {}", e)))?;
+        Ok(audio)
+    }
 
-        for &sample in samples {
-            // Convert f32 [-1.0, 1.0] to i16
-            let sample_i16 = (sample * i16::MAX as f32) as i16;
-            writer
-                .write_sample(sample_i16)
-                .map_err(|e| AppError::AudioRecording(format!("Failed to write sample: {}", e)))?;
+    /// Take the captured audio and convert it to the target format
+    ///
+    /// Taking the buffer means a second stop cannot process the same audio
+    /// again. Returns `None` if nothing was captured.
+    fn take_prepared(&mut self) -> Result<Option<AudioData>> {
+        let buffer = std::mem::take(&mut *self.buffer.lock().unwrap());
+        if buffer.is_empty() {
+            return Ok(None);
         }
 
-        writer
-            .finalize()
-            .map_err(|e| AppError::AudioRecording(format!("Failed to finalize WAV file: {}", e)))?;
+        let format = self.source.format();
+        let captured = AudioData::new(buffer, format.sample_rate, format.channels);
+        log::info!(
+            "Recorded {} samples ({:.2} seconds at {} Hz, {} channels)",
+            captured.samples.len(),
+            captured.duration_seconds(),
+            format.sample_rate,
+            format.channels
+        );
 
-        Ok(())
+        prepare_audio(&captured, &self.settings).map(Some)
     }
 
-    /// Build audio input stream for f32 samples
-    fn build_stream_f32(
-        &self,
-        buffer: Arc<Mutex<Vec<f32>>>,
-        is_recording: Arc<Mutex<bool>>,
-        err_fn: impl FnMut(cpal::StreamError) + Send + 'static,
-    ) -> Result<Stream> {
-        let stream = self
-            .device
-            .build_input_stream(
-                &self.config,
-                move |data: &[f32], _: &_| {
-                    if *is_recording.lock().unwrap() {
-                        let mut buf = buffer.lock().unwrap();
-                        buf.extend_from_slice(data);
-                    }
-                },
-                err_fn,
-                None,
-            )
-            .map_err(|e| AppError::AudioRecording(format!("Failed to build stream: {}", e)))?;
-
-        Ok(stream)
-    }
-
-    /// Build audio input stream for i16 samples
-    fn build_stream_i16(
-        &self,
-        buffer: Arc<Mutex<Vec<f32>>>,
-        is_recording: Arc<Mutex<bool>>,
-        err_fn: impl FnMut(cpal::StreamError) + Send + 'static,
-    ) -> Result<Stream> {
-        let stream = self
-            .device
-            .build_input_stream(
-                &self.config,
-                move |data: &[i16], _: &_| {
-                    if *is_recording.lock().unwrap() {
-                        let mut buf = buffer.lock().unwrap();
-                        // Convert i16 to f32 by normalizing
-                        buf.extend(data.iter().map(|&s| s as f32 / i16::MAX as f32));
-                    }
-                },
-                err_fn,
-                None,
-            )
-            .map_err(|e| AppError::AudioRecording(format!("Failed to build stream: {}", e)))?;
-
-        Ok(stream)
-    }
-
-    /// Build audio input stream for u16 samples
-    fn build_stream_u16(
-        &self,
-        buffer: Arc<Mutex<Vec<f32>>>,
-        is_recording: Arc<Mutex<bool>>,
-        err_fn: impl FnMut(cpal::StreamError) + Send + 'static,
-    ) -> Result<Stream> {
-        let stream = self
-            .device
-            .build_input_stream(
-                &self.config,
-                move |data: &[u16], _: &_| {
-                    if *is_recording.lock().unwrap() {
-                        let mut buf = buffer.lock().unwrap();
-                        // Convert u16 to f32 by normalizing (u16 is 0-65535, convert to -1.0 to 1.0)
-                        buf.extend(data.iter().map(|&s| (s as f32 / u16::MAX as f32) * 2.0 - 1.0));
-                    }
-                },
-                err_fn,
-                None,
-            )
-            .map_err(|e| AppError::AudioRecording(format!("Failed to build stream: {}", e)))?;
-
-        Ok(stream)
+    /// Stop recording and discard the captured audio
+    pub fn cancel_recording(&mut self) {
+        self.end_session();
+        self.buffer.lock().unwrap().clear();
+        log::info!("Recording cancelled");
     }
 }
 
 impl Drop for AudioRecorder {
     fn drop(&mut self) {
-        *self.is_recording.lock().unwrap() = false;
+        self.session.lock().unwrap().take();
         if let Some(stream) = self.stream.take() {
             drop(stream);
         }
@@ -227,9 +675,285 @@ impl Drop for AudioRecorder {
 mod tests {
     use super::*;
 
+    use source::FileSource;
+
+    fn tone(rate: u32, channels: u16, seconds: f32) -> Vec<f32> {
+        (0..(rate as f32 * seconds) as usize)
+            .flat_map(|i| {
+                let s = (2.0 * std::f32::consts::PI * 440.0 * i as f32 / rate as f32).sin() * 0.5;
+                std::iter::repeat_n(s, channels as usize)
+            })
+            .collect()
+    }
+
+    /// Recorder fed from a synthetic clip instead of a microphone
+    fn clip_recorder(audio: AudioData, config: &AudioConfig) -> AudioRecorder {
+        AudioRecorder::with_source(Box::new(FileSource::new("clip", audio)), config)
+    }
+
     #[test]
     fn test_audio_recorder_creation() {
-        let recorder = AudioRecorder::new();
-        assert!(recorder.is_ok());
+        let recorder = clip_recorder(AudioData::new(Vec::new(), 16000, 1), &AudioConfig::default());
+        assert!(!recorder.is_recording());
+    }
+
+    #[test]
+    fn test_recording_is_converted_to_output_format() {
+        let config = AudioConfig {
+            vad_enabled: false,
+            ..AudioConfig::default()
+        };
+        let mut recorder = clip_recorder(AudioData::new(tone(48000, 2, 1.0), 48000, 2), &config);
+
+        let mut events = recorder.start_recording().unwrap();
+        assert!(recorder.is_recording());
+        let audio = recorder.stop_recording().unwrap();
+
+        assert_eq!(audio.sample_rate, 16000);
+        assert_eq!(audio.channels, 1);
+        assert!((audio.duration_seconds() - 1.0).abs() < 0.01);
+        assert_eq!(next_control_event(&mut events), Some(RecorderEvent::InputEnded));
+        assert!(next_control_event(&mut events).is_none());
+
+        let wav = audio.to_wav_bytes().unwrap();
+        let reader = hound::WavReader::new(std::io::Cursor::new(wav)).unwrap();
+        assert_eq!(reader.spec().sample_rate, 16000);
+        assert_eq!(reader.len() as usize, audio.samples.len());
+    }
+
+    #[test]
+    fn test_aborted_recording_is_converted_to_output_format() {
+        let config = AudioConfig {
+            vad_enabled: false,
+            ..AudioConfig::default()
+        };
+        let mut recorder = clip_recorder(AudioData::new(tone(48000, 2, 1.0), 48000, 2), &config);
+
+        recorder.start_recording().unwrap();
+        let audio = recorder.abort_recording().unwrap().unwrap();
+
+        assert!(!recorder.is_recording());
+        assert_eq!((audio.sample_rate, audio.channels), (16000, 1));
+        assert!((audio.duration_seconds() - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_recording_stops_at_time_limit() {
+        let config = AudioConfig {
+            vad_enabled: false,
+            max_recording_duration_seconds: 2,
+            duration_warning_seconds: 0,
+            ..AudioConfig::default()
+        };
+        let mut recorder = clip_recorder(AudioData::new(tone(16000, 1, 5.0), 16000, 1), &config);
+
+        let mut events = recorder.start_recording().unwrap();
+        assert_eq!(
+            next_control_event(&mut events).unwrap(),
+            RecorderEvent::AutoStop(AutoStopReason::TimeLimit)
+        );
+
+        let audio = recorder.stop_recording().unwrap();
+        assert_eq!(audio.samples.len(), 32000);
+    }
+
+    #[test]
+    fn test_clip_is_not_played_into_pre_roll() {
+        let config = AudioConfig {
+            vad_enabled: false,
+            pre_roll_enabled: true,
+            ..AudioConfig::default()
+        };
+        let mut recorder = clip_recorder(AudioData::new(tone(16000, 1, 1.0), 16000, 1), &config);
+        assert!(recorder.stream.is_none());
+
+        recorder.start_recording().unwrap();
+        let audio = recorder.stop_recording().unwrap();
+        assert_eq!(audio.samples.len(), 16000);
+        assert!(recorder.stream.is_none());
+    }
+
+    #[test]
+    fn test_empty_recording_stops_cleanly() {
+        let mut recorder = clip_recorder(
+            AudioData::new(Vec::new(), 16000, 1),
+            &AudioConfig::default(),
+        );
+
+        recorder.start_recording().unwrap();
+        assert!(recorder.stop_recording().unwrap().is_empty());
+        // A second stop finds nothing either, without failing
+        assert!(recorder.stop_recording().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_silent_recording_has_no_speech() {
+        let mut recorder =
+            clip_recorder(AudioData::new(vec![0.0; 32000], 16000, 1), &AudioConfig::default());
+
+        recorder.start_recording().unwrap();
+        assert!(recorder.stop_recording().unwrap().is_empty());
+        assert!(!recorder.is_recording());
+    }
+
+    #[test]
+    fn test_unknown_device_falls_back_to_default() {
+        let config = AudioConfig {
+            device_id: "no such device".to_string(),
+            ..AudioConfig::default()
+        };
+
+        // Without any input device both fail alike, on the missing default
+        match (AudioRecorder::new(&config), source::DeviceSource::new("")) {
+            (Ok(recorder), Ok(default)) => {
+                assert!(recorder.uses_input_of(&config));
+                assert_eq!(recorder.source.name(), default.name());
+            }
+            (Err(unknown), Err(default)) => {
+                assert_eq!(unknown.to_string(), default.to_string());
+            }
+            (unknown, default) => panic!(
+                "unknown device opened {:?}, default device opened {:?}",
+                unknown.map(|r| r.source.name()),
+                default.map(|d| d.name())
+            ),
+        }
+    }
+
+    fn limited_capture(max_seconds: u64, warning_seconds: u64) -> (Capture, RecorderEvents) {
+        let config = AudioConfig {
+            max_recording_duration_seconds: max_seconds,
+            duration_warning_seconds: warning_seconds,
+            ..AudioConfig::default()
+        };
+        let (events, receiver) = mpsc::unbounded_channel();
+        let capture = Capture {
+            buffer: Arc::new(Mutex::new(Vec::new())),
+            session: Arc::new(Mutex::new(Some(Session {
+                paused: false,
+                silence: None,
+                limit: DurationLimit::new(&config, 100, 2),
+                level: LevelMeter::new(100, 2),
+                events,
+            }))),
+            pre_roll: None,
+        };
+        (capture, receiver)
+    }
+
+    /// Next event that is not a level reading
+    fn next_control_event(events: &mut RecorderEvents) -> Option<RecorderEvent> {
+        std::iter::from_fn(|| events.try_recv().ok())
+            .find(|event| !matches!(event, RecorderEvent::Level(_)))
+    }
+
+    #[test]
+    fn test_time_limit_caps_buffer_and_stops() {
+        let (mut capture, mut events) = limited_capture(3, 1);
+
+        // 100 Hz stereo: one second is 200 samples
+        capture.push(&[0.1; 300]);
+        assert!(next_control_event(&mut events).is_none());
+
+        capture.push(&[0.1; 200]);
+        assert_eq!(
+            next_control_event(&mut events).unwrap(),
+            RecorderEvent::TimeLimitApproaching {
+                remaining_seconds: 1,
+                max_seconds: 3
+            }
+        );
+
+        capture.push(&[0.1; 500]);
+        assert_eq!(
+            next_control_event(&mut events).unwrap(),
+            RecorderEvent::AutoStop(AutoStopReason::TimeLimit)
+        );
+        assert_eq!(capture.buffer.lock().unwrap().len(), 600);
+
+        // Further audio is dropped and the stop is only requested once
+        capture.push(&[0.1; 500]);
+        assert_eq!(capture.buffer.lock().unwrap().len(), 600);
+        assert!(next_control_event(&mut events).is_none());
+    }
+
+    #[test]
+    fn test_zero_time_limit_is_unlimited() {
+        let (mut capture, mut events) = limited_capture(0, 30);
+
+        capture.push(&[0.1; 10_000]);
+
+        assert!(capture.session.lock().unwrap().as_ref().unwrap().limit.is_none());
+        assert_eq!(capture.buffer.lock().unwrap().len(), 10_000);
+        assert!(next_control_event(&mut events).is_none());
+    }
+
+    #[test]
+    fn test_pre_roll_is_prepended_to_recording() {
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        let session = Arc::new(Mutex::new(None));
+        let mut capture = Capture {
+            buffer: Arc::clone(&buffer),
+            session: Arc::clone(&session),
+            // 100 Hz mono: 20 ms is 2 samples
+            pre_roll: Some(PreRollBuffer::new(100, 1, 20)),
+        };
+
+        // Between recordings audio only feeds the pre-roll
+        capture.push(&[0.1, 0.2, 0.3]);
+        assert!(buffer.lock().unwrap().is_empty());
+
+        let (events, _receiver) = mpsc::unbounded_channel();
+        *session.lock().unwrap() = Some(Session {
+            paused: false,
+            silence: None,
+            limit: None,
+            level: LevelMeter::new(100, 1),
+            events,
+        });
+        capture.push(&[0.4, 0.5]);
+        assert_eq!(*buffer.lock().unwrap(), vec![0.2, 0.3, 0.4, 0.5]);
+
+        // Audio from a pause is neither recorded nor kept for the resume
+        session.lock().unwrap().as_mut().unwrap().paused = true;
+        capture.push(&[0.6, 0.7]);
+        session.lock().unwrap().as_mut().unwrap().paused = false;
+        capture.push(&[0.8]);
+
+        assert_eq!(*buffer.lock().unwrap(), vec![0.2, 0.3, 0.4, 0.5, 0.8]);
+    }
+
+    #[test]
+    fn test_paused_input_is_not_recorded() {
+        let (mut capture, mut events) = limited_capture(3, 0);
+
+        capture.push(&[0.1; 100]);
+        capture.session.lock().unwrap().as_mut().unwrap().paused = true;
+        capture.push(&[0.2; 1000]);
+        capture.session.lock().unwrap().as_mut().unwrap().paused = false;
+        capture.push(&[0.3; 100]);
+
+        // Paused audio does not count towards the time limit either
+        let buffer = capture.buffer.lock().unwrap();
+        assert_eq!(buffer.len(), 200);
+        assert!(buffer.iter().all(|&s| s != 0.2));
+        assert!(next_control_event(&mut events).is_none());
+    }
+
+    #[test]
+    fn test_stream_error_is_reported_once() {
+        let (capture, mut events) = limited_capture(0, 0);
+        let failed = Arc::new(AtomicBool::new(false));
+        let mut on_error = stream_error_handler(Arc::clone(&capture.session), Arc::clone(&failed));
+
+        on_error("The input device was disconnected".to_string());
+        on_error("The input device was disconnected".to_string());
+
+        assert!(failed.load(Ordering::SeqCst));
+        assert_eq!(
+            next_control_event(&mut events).unwrap(),
+            RecorderEvent::StreamFailed("The input device was disconnected".to_string())
+        );
+        assert!(next_control_event(&mut events).is_none());
     }
 }
@@ -0,0 +1,72 @@
+use std::collections::VecDeque;
+
+/// Rolling buffer of the most recent input, kept while no recording is running
+///
+/// Prepended to the next recording so speech that starts just before the
+/// hotkey is pressed is not clipped.
+#[derive(Debug, Clone)]
+pub struct PreRollBuffer {
+    samples: VecDeque<f32>,
+    capacity: usize,
+}
+
+impl PreRollBuffer {
+    pub fn new(sample_rate: u32, channels: u16, duration_ms: u32) -> Self {
+        let channels = channels.max(1) as usize;
+        let frames = (sample_rate as u64 * duration_ms as u64 / 1000) as usize;
+        let capacity = frames * channels;
+
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Add interleaved samples, dropping the oldest once full
+    pub fn push(&mut self, samples: &[f32]) {
+        let samples = &samples[samples.len().saturating_sub(self.capacity)..];
+        let overflow = (self.samples.len() + samples.len()).saturating_sub(self.capacity);
+        self.samples.drain(..overflow);
+        self.samples.extend(samples);
+    }
+
+    /// Move the buffered audio to the end of `buffer`, oldest first
+    pub fn drain_into(&mut self, buffer: &mut Vec<f32>) {
+        buffer.extend(self.samples.drain(..));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keeps_most_recent_samples() {
+        // 10 ms of 1 kHz stereo is 20 samples
+        let mut pre_roll = PreRollBuffer::new(1000, 2, 10);
+
+        pre_roll.push(&(0..15).map(|i| i as f32).collect::<Vec<_>>());
+        pre_roll.push(&(15..30).map(|i| i as f32).collect::<Vec<_>>());
+
+        let mut buffer = vec![-1.0];
+        pre_roll.drain_into(&mut buffer);
+
+        let expected: Vec<f32> = std::iter::once(-1.0).chain((10..30).map(|i| i as f32)).collect();
+        assert_eq!(buffer, expected);
+
+        // Draining empties the pre-roll
+        pre_roll.drain_into(&mut buffer);
+        assert_eq!(buffer.len(), 21);
+    }
+
+    #[test]
+    fn test_oversized_push_keeps_tail() {
+        let mut pre_roll = PreRollBuffer::new(1000, 1, 5);
+
+        pre_roll.push(&[0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]);
+
+        let mut buffer = Vec::new();
+        pre_roll.drain_into(&mut buffer);
+        assert_eq!(buffer, vec![3.0, 4.0, 5.0, 6.0, 7.0]);
+    }
+}
@@ -0,0 +1,287 @@
+use std::f64::consts::PI;
+
+/// Number of zero crossings of the sinc kernel on each side of the output sample
+const SINC_ZERO_CROSSINGS: usize = 16;
+
+/// Convert interleaved audio to the requested channel count and sample rate
+pub fn convert(
+    samples: &[f32],
+    src_channels: u16,
+    src_rate: u32,
+    dst_channels: u16,
+    dst_rate: u32,
+) -> Vec<f32> {
+    let remixed = remix_channels(samples, src_channels, dst_channels);
+
+    if src_rate == dst_rate || remixed.is_empty() {
+        return remixed;
+    }
+
+    let channels = dst_channels.max(1) as usize;
+    if channels == 1 {
+        return resample_mono(&remixed, src_rate, dst_rate);
+    }
+
+    // Resample each channel separately and re-interleave
+    let planar: Vec<Vec<f32>> = (0..channels)
+        .map(|ch| {
+            let channel: Vec<f32> = remixed.iter().skip(ch).step_by(channels).copied().collect();
+            resample_mono(&channel, src_rate, dst_rate)
+        })
+        .collect();
+
+    let frames = planar.iter().map(Vec::len).min().unwrap_or(0);
+    let mut output = Vec::with_capacity(frames * channels);
+    for frame in 0..frames {
+        for channel in &planar {
+            output.push(channel[frame]);
+        }
+    }
+    output
+}
+
+/// Remix interleaved audio from `src_channels` to `dst_channels`
+///
+/// Downmixing to mono averages all channels. Upmixing from mono duplicates the
+/// signal. Other layouts keep the first channels and pad with silence.
+pub fn remix_channels(samples: &[f32], src_channels: u16, dst_channels: u16) -> Vec<f32> {
+    let src = src_channels.max(1) as usize;
+    let dst = dst_channels.max(1) as usize;
+
+    if src == dst {
+        return samples.to_vec();
+    }
+
+    let frames = samples.chunks_exact(src);
+    let mut output = Vec::with_capacity(frames.len() * dst);
+
+    for frame in frames {
+        if dst == 1 {
+            output.push(frame.iter().sum::<f32>() / src as f32);
+        } else if src == 1 {
+            output.extend(std::iter::repeat_n(frame[0], dst));
+        } else {
+            output.extend((0..dst).map(|ch| frame.get(ch).copied().unwrap_or(0.0)));
+        }
+    }
+
+    output
+}
+
+/// Resample a single channel with a Blackman-windowed sinc filter
+///
+/// When downsampling the kernel cutoff is lowered to the target Nyquist
+/// frequency so content above it is removed instead of aliasing into speech.
+/// The kernel is computed once per phase up front, so the work per output
+/// sample is a plain multiply-add over the taps.
+pub fn resample_mono(samples: &[f32], src_rate: u32, dst_rate: u32) -> Vec<f32> {
+    if src_rate == dst_rate || samples.is_empty() || src_rate == 0 || dst_rate == 0 {
+        return samples.to_vec();
+    }
+
+    let kernel = PolyphaseKernel::new(src_rate, dst_rate);
+    let output_len = (samples.len() as f64 * dst_rate as f64 / src_rate as f64).round() as usize;
+    let last_index = samples.len() as i64 - 1;
+
+    let mut output = Vec::with_capacity(output_len);
+    for n in 0..output_len {
+        let (base, weights) = kernel.taps(n as u64);
+        let first = base - kernel.reach;
+
+        let mut acc = 0.0;
+        let mut weight_sum = 0.0;
+        for (j, &weight) in weights.iter().enumerate() {
+            let k = first + j as i64;
+            if k < 0 || k > last_index {
+                continue;
+            }
+            acc += samples[k as usize] as f64 * weight;
+            weight_sum += weight;
+        }
+
+        let value = if weight_sum.abs() > f64::EPSILON {
+            acc / weight_sum
+        } else {
+            0.0
+        };
+        output.push(value as f32);
+    }
+
+    output
+}
+
+/// Most filter phases kept in the table; odd rate pairs are rounded to the nearest
+const MAX_PHASES: u64 = 1024;
+
+/// Filter weights for every fractional position an output sample can fall on
+///
+/// With the rate ratio reduced to `up / down`, output sample `n` sits at input
+/// position `n * down / up`, so only `up` distinct fractions occur.
+struct PolyphaseKernel {
+    up: u64,
+    down: u64,
+    phases: u64,
+    /// Taps on each side of the centre, in input samples
+    reach: i64,
+    /// `phases` rows of `2 * reach + 1` weights
+    weights: Vec<f64>,
+}
+
+impl PolyphaseKernel {
+    fn new(src_rate: u32, dst_rate: u32) -> Self {
+        let divisor = gcd(src_rate as u64, dst_rate as u64);
+        let up = dst_rate as u64 / divisor;
+        let down = src_rate as u64 / divisor;
+        let phases = up.min(MAX_PHASES);
+
+        let cutoff = (dst_rate as f64 / src_rate as f64).min(1.0);
+        // Kernel half-width measured in input samples
+        let half_width = SINC_ZERO_CROSSINGS as f64 / cutoff;
+        let reach = half_width.ceil() as i64;
+        let width = 2 * reach as usize + 1;
+
+        let mut weights = Vec::with_capacity(phases as usize * width);
+        for phase in 0..phases {
+            let fraction = phase as f64 / phases as f64;
+            for j in 0..width {
+                let distance = fraction + reach as f64 - j as f64;
+                weights.push(cutoff * sinc(cutoff * distance) * blackman(distance / half_width));
+            }
+        }
+
+        Self {
+            up,
+            down,
+            phases,
+            reach,
+            weights,
+        }
+    }
+
+    /// Input sample the output sample `n` is centred after, and its weights
+    fn taps(&self, n: u64) -> (i64, &[f64]) {
+        let position = n * self.down;
+        let mut base = (position / self.up) as i64;
+        let mut phase = (position % self.up * self.phases + self.up / 2) / self.up;
+        if phase == self.phases {
+            base += 1;
+            phase = 0;
+        }
+
+        let width = 2 * self.reach as usize + 1;
+        let start = phase as usize * width;
+        (base, &self.weights[start..start + width])
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-9 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+/// Blackman window over `x` in [-1, 1]
+fn blackman(x: f64) -> f64 {
+    if x.abs() >= 1.0 {
+        return 0.0;
+    }
+    let phase = PI * (x + 1.0);
+    0.42 - 0.5 * phase.cos() + 0.08 * (2.0 * phase).cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(freq: f32, rate: u32, seconds: f32) -> Vec<f32> {
+        let len = (rate as f32 * seconds) as usize;
+        (0..len)
+            .map(|i| (2.0 * std::f32::consts::PI * freq * i as f32 / rate as f32).sin() * 0.5)
+            .collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn test_downmix_stereo_to_mono_averages() {
+        let stereo = [1.0, 0.0, 0.5, 0.5, -1.0, 1.0];
+        assert_eq!(remix_channels(&stereo, 2, 1), vec![0.5, 0.5, 0.0]);
+    }
+
+    #[test]
+    fn test_upmix_mono_to_stereo_duplicates() {
+        let mono = [0.25, -0.5];
+        assert_eq!(remix_channels(&mono, 1, 2), vec![0.25, 0.25, -0.5, -0.5]);
+    }
+
+    #[test]
+    fn test_same_rate_is_passthrough() {
+        let samples = sine(440.0, 16000, 0.1);
+        assert_eq!(resample_mono(&samples, 16000, 16000), samples);
+    }
+
+    #[test]
+    fn test_downsample_length_and_level() {
+        let samples = sine(440.0, 48000, 1.0);
+        let output = resample_mono(&samples, 48000, 16000);
+
+        assert_eq!(output.len(), 16000);
+        // A tone well below the new Nyquist keeps its level
+        let (a, b) = (rms(&samples), rms(&output[1000..15000]));
+        assert!((a - b).abs() < 0.01, "rms {} vs {}", a, b);
+    }
+
+    #[test]
+    fn test_downsample_removes_content_above_nyquist() {
+        // 12 kHz is above the 8 kHz Nyquist of a 16 kHz output
+        let samples = sine(12000.0, 48000, 1.0);
+        let output = resample_mono(&samples, 48000, 16000);
+
+        assert!(rms(&output[1000..15000]) < 0.01);
+    }
+
+    #[test]
+    fn test_odd_rate_pair_keeps_level() {
+        let samples = sine(440.0, 44100, 1.0);
+        let output = resample_mono(&samples, 44100, 16000);
+
+        assert_eq!(output.len(), 16000);
+        let (a, b) = (rms(&samples), rms(&output[1000..15000]));
+        assert!((a - b).abs() < 0.01, "rms {} vs {}", a, b);
+    }
+
+    #[test]
+    fn test_upsample_matches_tone() {
+        let samples = sine(440.0, 16000, 0.5);
+        let output = resample_mono(&samples, 16000, 48000);
+
+        assert_eq!(output.len(), 24000);
+        let expected = sine(440.0, 48000, 0.5);
+        assert!(output[3000..21000]
+            .iter()
+            .zip(&expected[3000..21000])
+            .all(|(a, b)| (a - b).abs() < 0.01));
+    }
+
+    #[test]
+    fn test_convert_48k_stereo_to_16k_mono() {
+        let mono = sine(300.0, 48000, 0.5);
+        let stereo: Vec<f32> = mono.iter().flat_map(|&s| [s, s]).collect();
+
+        let output = convert(&stereo, 2, 48000, 1, 16000);
+
+        assert_eq!(output.len(), 8000);
+    }
+}
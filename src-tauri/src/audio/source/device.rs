@@ -0,0 +1,270 @@
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{Device, FromSample, Host, Sample, SampleFormat, SizedSample, Stream, StreamConfig};
+
+use super::{AudioSource, DataCallback, ErrorCallback, InputStream, SourceFormat};
+use crate::audio::DEFAULT_DEVICE_ID;
+use crate::error::{AppError, Result};
+
+/// Input device opened through cpal
+pub struct DeviceSource {
+    device_id: String,
+    /// Records what the system plays rather than a microphone
+    monitor: bool,
+    device: Device,
+    config: StreamConfig,
+    sample_format: SampleFormat,
+}
+
+impl InputStream for Stream {}
+
+impl DeviceSource {
+    /// Open the input device named by `device_id`, falling back to the default device
+    pub fn new(device_id: &str) -> Result<Self> {
+        Self::open_device(device_id, false)
+    }
+
+    /// Open a PulseAudio/PipeWire monitor source, which records system playback
+    ///
+    /// An empty `device_id` picks the first monitor found. There is no
+    /// fallback to the default device, since that would record the microphone.
+    pub fn monitor(device_id: &str) -> Result<Self> {
+        Self::open_device(device_id, true)
+    }
+
+    fn open_device(device_id: &str, monitor: bool) -> Result<Self> {
+        let host = cpal::default_host();
+        let device = if monitor {
+            select_monitor_device(&host, device_id)?
+        } else {
+            select_input_device(&host, device_id)?
+        };
+
+        log::info!("Using audio device: {}", device.name().unwrap_or_default());
+
+        let supported_config = device.default_input_config().map_err(|e| {
+            AppError::AudioRecording(format!("Failed to get default config: {}", e))
+        })?;
+
+        Ok(Self {
+            device_id: device_id.to_string(),
+            monitor,
+            device,
+            config: supported_config.config(),
+            sample_format: supported_config.sample_format(),
+        })
+    }
+
+    /// Capture in `format` instead of the device default, if the device supports it
+    ///
+    /// Returns whether the format is now in use.
+    pub fn prefer_format(&mut self, format: SourceFormat) -> bool {
+        let Ok(mut configs) = self.device.supported_input_configs() else {
+            return false;
+        };
+        let rate = cpal::SampleRate(format.sample_rate);
+        let Some(range) = configs.find(|c| {
+            c.channels() == format.channels
+                && c.min_sample_rate() <= rate
+                && rate <= c.max_sample_rate()
+        }) else {
+            return false;
+        };
+
+        let supported = range.with_sample_rate(rate);
+        self.config = supported.config();
+        self.sample_format = supported.sample_format();
+        true
+    }
+
+    /// Build an input stream for samples of type `T`, converted to f32
+    fn build_stream<T>(&self, mut on_data: DataCallback, on_error: ErrorCallback) -> Result<Stream>
+    where
+        T: SizedSample,
+        f32: FromSample<T>,
+    {
+        let mut samples = Vec::new();
+        let stream = self
+            .device
+            .build_input_stream(
+                &self.config,
+                move |data: &[T], _: &_| {
+                    convert_samples(data, &mut samples);
+                    on_data(&samples);
+                },
+                error_callback(on_error),
+                None,
+            )
+            .map_err(|e| AppError::AudioRecording(format!("Failed to build stream: {}", e)))?;
+
+        Ok(stream)
+    }
+}
+
+impl AudioSource for DeviceSource {
+    fn name(&self) -> String {
+        self.device.name().unwrap_or_default()
+    }
+
+    fn format(&self) -> SourceFormat {
+        SourceFormat {
+            sample_rate: self.config.sample_rate.0,
+            channels: self.config.channels,
+        }
+    }
+
+    fn open(
+        &mut self,
+        on_data: DataCallback,
+        on_error: ErrorCallback,
+    ) -> Result<Box<dyn InputStream>> {
+        let stream = match self.sample_format {
+            SampleFormat::I8 => self.build_stream::<i8>(on_data, on_error)?,
+            SampleFormat::I16 => self.build_stream::<i16>(on_data, on_error)?,
+            SampleFormat::I32 => self.build_stream::<i32>(on_data, on_error)?,
+            SampleFormat::I64 => self.build_stream::<i64>(on_data, on_error)?,
+            SampleFormat::U8 => self.build_stream::<u8>(on_data, on_error)?,
+            SampleFormat::U16 => self.build_stream::<u16>(on_data, on_error)?,
+            SampleFormat::U32 => self.build_stream::<u32>(on_data, on_error)?,
+            SampleFormat::U64 => self.build_stream::<u64>(on_data, on_error)?,
+            SampleFormat::F32 => self.build_stream::<f32>(on_data, on_error)?,
+            SampleFormat::F64 => self.build_stream::<f64>(on_data, on_error)?,
+            other => {
+                return Err(AppError::AudioRecording(format!(
+                    "Unsupported sample format: {}",
+                    other
+                )))
+            }
+        };
+
+        stream
+            .play()
+            .map_err(|e| AppError::AudioRecording(format!("Failed to play stream: {}", e)))?;
+
+        Ok(Box::new(stream))
+    }
+
+    fn reselect(&mut self) -> Result<()> {
+        *self = Self::open_device(&self.device_id, self.monitor)?;
+        Ok(())
+    }
+}
+
+/// Convert device samples to f32 in [-1.0, 1.0], replacing `output`
+///
+/// Integer formats are scaled symmetrically around their origin, so the most
+/// negative value maps to exactly -1.0. Float input outside the range is clipped.
+fn convert_samples<T>(input: &[T], output: &mut Vec<f32>)
+where
+    T: Sample,
+    f32: FromSample<T>,
+{
+    output.clear();
+    output.extend(input.iter().map(|&s| s.to_sample::<f32>().clamp(-1.0, 1.0)));
+}
+
+/// Adapt an error callback to cpal's error type
+fn error_callback(mut on_error: ErrorCallback) -> impl FnMut(cpal::StreamError) + Send + 'static {
+    move |err| on_error(stream_error_message(&err))
+}
+
+/// Describe a stream error for the user
+fn stream_error_message(err: &cpal::StreamError) -> String {
+    match err {
+        cpal::StreamError::DeviceNotAvailable => "The input device was disconnected".to_string(),
+        cpal::StreamError::BackendSpecific { err } => err.to_string(),
+    }
+}
+
+/// Find the input device named by `device_id`, falling back to the default device
+fn select_input_device(host: &Host, device_id: &str) -> Result<Device> {
+    if !device_id.is_empty() && device_id != DEFAULT_DEVICE_ID {
+        let found = host
+            .input_devices()
+            .map_err(|e| AppError::AudioRecording(format!("Failed to enumerate devices: {}", e)))?
+            .find(|device| device.name().is_ok_and(|n| n == device_id));
+
+        match found {
+            Some(device) => return Ok(device),
+            None => log::warn!(
+                "Input device '{}' not found, falling back to default device",
+                device_id
+            ),
+        }
+    }
+
+    host.default_input_device()
+        .ok_or_else(|| AppError::AudioRecording("No input device available".to_string()))
+}
+
+/// Find the monitor source named by `device_id`, or the first one if it is empty
+fn select_monitor_device(host: &Host, device_id: &str) -> Result<Device> {
+    let mut devices = host
+        .input_devices()
+        .map_err(|e| AppError::AudioRecording(format!("Failed to enumerate devices: {}", e)))?;
+
+    let found = if device_id.is_empty() {
+        devices.find(|device| device.name().is_ok_and(|n| is_monitor_name(&n)))
+    } else {
+        devices.find(|device| device.name().is_ok_and(|n| n == device_id))
+    };
+
+    found.ok_or_else(|| {
+        AppError::AudioRecording(if device_id.is_empty() {
+            "No system audio monitor source found".to_string()
+        } else {
+            format!("Monitor source '{}' not found", device_id)
+        })
+    })
+}
+
+/// Whether a device name looks like a PulseAudio/PipeWire monitor source
+pub fn is_monitor_name(name: &str) -> bool {
+    name.to_lowercase().contains("monitor")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unplugged_device_message() {
+        assert_eq!(
+            stream_error_message(&cpal::StreamError::DeviceNotAvailable),
+            "The input device was disconnected"
+        );
+    }
+
+    #[test]
+    fn test_integer_formats_scale_symmetrically() {
+        let mut output = Vec::new();
+
+        convert_samples(&[i16::MIN, 0, 16384], &mut output);
+        assert_eq!(output, vec![-1.0, 0.0, 0.5]);
+
+        convert_samples(&[i32::MIN, i32::MAX], &mut output);
+        assert_eq!(output[0], -1.0);
+        assert!((output[1] - 1.0).abs() < 1e-6);
+
+        // Unsigned formats are centred on their midpoint
+        convert_samples(&[0u8, 128, 255], &mut output);
+        assert_eq!(output[0], -1.0);
+        assert_eq!(output[1], 0.0);
+        assert!(output[2] > 0.99);
+    }
+
+    #[test]
+    fn test_float_formats_saturate() {
+        let mut output = Vec::new();
+        convert_samples(&[1.5f64, -2.0, 0.25], &mut output);
+        assert_eq!(output, vec![1.0, -1.0, 0.25]);
+    }
+
+    #[test]
+    fn test_monitor_names() {
+        assert!(is_monitor_name("Monitor of Built-in Audio Analog Stereo"));
+        assert!(is_monitor_name(
+            "alsa_output.pci-0000_00_1f.3.analog-stereo.monitor"
+        ));
+        assert!(!is_monitor_name("Built-in Audio Analog Stereo"));
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use super::{AudioSource, DataCallback, DeviceSource, ErrorCallback, InputStream, SourceFormat};
+use crate::audio::resample;
+use crate::error::Result;
+
+/// Most system audio held while waiting for the microphone to catch up
+const MAX_PENDING_MS: u32 = 500;
+
+/// Records the microphone and system playback together
+///
+/// The microphone drives the stream: system audio is queued as it arrives
+/// and added to each microphone buffer. Both are halved so the sum cannot
+/// clip; normalization brings the level back up afterwards.
+pub struct MixedSource {
+    microphone: DeviceSource,
+    system: DeviceSource,
+}
+
+/// Both streams of a [`MixedSource`]
+struct MixedStream {
+    _microphone: Box<dyn InputStream>,
+    _system: Box<dyn InputStream>,
+}
+
+impl InputStream for MixedStream {}
+
+impl MixedSource {
+    pub fn new(microphone: DeviceSource, mut system: DeviceSource) -> Self {
+        // Matching formats avoid converting system audio in small pieces
+        if !system.prefer_format(microphone.format()) {
+            log::warn!("System audio will be converted to the microphone format while recording");
+        }
+        Self { microphone, system }
+    }
+}
+
+impl AudioSource for MixedSource {
+    fn name(&self) -> String {
+        format!("{} + {}", self.microphone.name(), self.system.name())
+    }
+
+    fn format(&self) -> SourceFormat {
+        self.microphone.format()
+    }
+
+    fn open(
+        &mut self,
+        mut on_data: DataCallback,
+        on_error: ErrorCallback,
+    ) -> Result<Box<dyn InputStream>> {
+        let target = self.microphone.format();
+        let from = self.system.format();
+        let mixer = Arc::new(Mutex::new(Mixer::new(target)));
+        let on_error = Arc::new(Mutex::new(on_error));
+
+        let system_mixer = Arc::clone(&mixer);
+        let system = self.system.open(
+            Box::new(move |samples| {
+                if from == target {
+                    system_mixer.lock().unwrap().push(samples);
+                } else {
+                    let converted = resample::convert(
+                        samples,
+                        from.channels,
+                        from.sample_rate,
+                        target.channels,
+                        target.sample_rate,
+                    );
+                    system_mixer.lock().unwrap().push(&converted);
+                }
+            }),
+            forward_errors(&on_error, "System audio"),
+        )?;
+
+        let mut mixed = Vec::new();
+        let microphone = self.microphone.open(
+            Box::new(move |samples| {
+                mixer.lock().unwrap().mix(samples, &mut mixed);
+                on_data(&mixed);
+            }),
+            forward_errors(&on_error, "Microphone"),
+        )?;
+
+        Ok(Box::new(MixedStream {
+            _microphone: microphone,
+            _system: system,
+        }))
+    }
+
+    fn reselect(&mut self) -> Result<()> {
+        self.microphone.reselect()?;
+        self.system.reselect()?;
+        self.system.prefer_format(self.microphone.format());
+        Ok(())
+    }
+}
+
+/// Share one error callback between both streams, naming the input that failed
+fn forward_errors(on_error: &Arc<Mutex<ErrorCallback>>, input: &'static str) -> ErrorCallback {
+    let on_error = Arc::clone(on_error);
+    Box::new(move |message| (on_error.lock().unwrap())(format!("{}: {}", input, message)))
+}
+
+/// System audio waiting to be added to the microphone signal
+struct Mixer {
+    pending: VecDeque<f32>,
+    capacity: usize,
+}
+
+impl Mixer {
+    fn new(format: SourceFormat) -> Self {
+        let frames = (format.sample_rate as u64 * MAX_PENDING_MS as u64 / 1000) as usize;
+        Self {
+            pending: VecDeque::new(),
+            capacity: frames * format.channels.max(1) as usize,
+        }
+    }
+
+    /// Queue system audio, dropping the oldest if the microphone has stalled
+    fn push(&mut self, samples: &[f32]) {
+        self.pending.extend(samples);
+        let overflow = self.pending.len().saturating_sub(self.capacity);
+        self.pending.drain(..overflow);
+    }
+
+    /// Mix queued system audio into microphone samples, replacing `output`
+    ///
+    /// System audio that has not arrived yet counts as silence.
+    fn mix(&mut self, microphone: &[f32], output: &mut Vec<f32>) {
+        output.clear();
+        output.extend(
+            microphone
+                .iter()
+                .map(|&mic| (mic + self.pending.pop_front().unwrap_or(0.0)) * 0.5),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: &[f32], expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-6, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    fn mixer() -> Mixer {
+        // 500 ms of 10 Hz mono is 5 samples
+        Mixer::new(SourceFormat {
+            sample_rate: 10,
+            channels: 1,
+        })
+    }
+
+    #[test]
+    fn test_mixes_queued_system_audio() {
+        let mut mixer = mixer();
+        let mut output = Vec::new();
+
+        mixer.push(&[0.2, 0.4]);
+        mixer.mix(&[0.6, 0.0, 1.0], &mut output);
+
+        assert_close(&output, &[0.4, 0.2, 0.5]);
+    }
+
+    #[test]
+    fn test_stalled_microphone_drops_oldest_system_audio() {
+        let mut mixer = mixer();
+        let mut output = Vec::new();
+
+        mixer.push(&[0.1; 4]);
+        mixer.push(&[0.3; 4]);
+        mixer.mix(&[0.0; 5], &mut output);
+
+        assert_close(&output, &[0.05, 0.15, 0.15, 0.15, 0.15]);
+    }
+}
@@ -0,0 +1,80 @@
+mod device;
+mod file;
+mod mixed;
+
+pub use device::{is_monitor_name, DeviceSource};
+pub use file::FileSource;
+pub use mixed::MixedSource;
+
+use std::path::Path;
+
+use crate::config::{AudioConfig, CaptureMode};
+use crate::error::Result;
+
+/// Device ids with this prefix replay an audio file instead of opening a device
+pub const FILE_DEVICE_PREFIX: &str = "file:";
+
+/// Sample rate and channel count of the audio a source delivers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceFormat {
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+/// Receives interleaved f32 samples as they arrive
+pub type DataCallback = Box<dyn FnMut(&[f32]) + Send + 'static>;
+
+/// Receives a description of the failure when the stream breaks
+pub type ErrorCallback = Box<dyn FnMut(String) + Send + 'static>;
+
+/// A running input stream; delivery stops when it is dropped
+pub trait InputStream {}
+
+/// Where the recorder gets its input from
+pub trait AudioSource {
+    /// Name shown to the user, e.g. the device name
+    fn name(&self) -> String;
+
+    /// Format of the samples passed to the data callback
+    fn format(&self) -> SourceFormat;
+
+    /// Start delivering samples
+    fn open(
+        &mut self,
+        on_data: DataCallback,
+        on_error: ErrorCallback,
+    ) -> Result<Box<dyn InputStream>>;
+
+    /// Pick the input again after a stream error
+    ///
+    /// The format may change, e.g. when falling back to another device.
+    fn reselect(&mut self) -> Result<()>;
+
+    /// Whether the source runs out, like a file, instead of capturing live
+    ///
+    /// A finite source delivers its audio when opened and nothing after that.
+    fn is_finite(&self) -> bool {
+        false
+    }
+}
+
+/// Open the source selected by the capture mode and device settings
+///
+/// A `file:<path>` device id replays an audio file in place of the microphone.
+pub fn open_source(config: &AudioConfig) -> Result<Box<dyn AudioSource>> {
+    let microphone = || -> Result<Box<dyn AudioSource>> {
+        match config.device_id.strip_prefix(FILE_DEVICE_PREFIX) {
+            Some(path) => Ok(Box::new(FileSource::open(Path::new(path))?)),
+            None => Ok(Box::new(DeviceSource::new(&config.device_id)?)),
+        }
+    };
+
+    match config.capture_mode {
+        CaptureMode::Microphone => microphone(),
+        CaptureMode::SystemAudio => Ok(Box::new(DeviceSource::monitor(&config.monitor_device_id)?)),
+        CaptureMode::Mixed => Ok(Box::new(MixedSource::new(
+            DeviceSource::new(&config.device_id)?,
+            DeviceSource::monitor(&config.monitor_device_id)?,
+        ))),
+    }
+}
@@ -0,0 +1,420 @@
+use std::fs::{DirBuilder, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use super::{decode, prepare_audio, AudioData};
+use crate::config::AudioConfig;
+use crate::error::{AppError, Result};
+use crate::utils::{self, crypto::SessionKey};
+
+/// Directory inside the app's runtime or cache dir that holds spooled recordings
+const SPOOL_DIR_NAME: &str = "spool";
+
+/// Prefix of recordings spooled straight into the shared temp dir by older versions
+const LEGACY_PREFIX: &str = "openwhisperflow_";
+
+/// Extension added to encrypted recordings
+const ENCRYPTED_EXTENSION: &str = "enc";
+
+/// Distinguishes recordings spooled within the same millisecond
+static SEQUENCE: AtomicU32 = AtomicU32::new(0);
+
+/// A recording kept on disk until it has been processed
+///
+/// Lets a recording outlive a crash during transcription. When a session key
+/// is given the file is encrypted with it, so leftovers hold no readable audio.
+/// Recordings are spooled already prepared, in the output format; only legacy
+/// files hold raw device audio.
+#[derive(Debug)]
+pub struct SpooledRecording {
+    path: PathBuf,
+}
+
+impl SpooledRecording {
+    /// Write prepared `audio` as a WAV file in the private spool directory
+    pub fn write(audio: &AudioData, key: Option<&SessionKey>) -> Result<Self> {
+        Self::write_in(&spool_dir()?, audio, key)
+    }
+
+    fn write_in(dir: &Path, audio: &AudioData, key: Option<&SessionKey>) -> Result<Self> {
+        let wav = audio.to_wav_bytes()?;
+        let bytes = match key {
+            Some(key) => key.seal(&wav)?,
+            None => wav,
+        };
+
+        let path = dir.join(unique_name(key.is_some()));
+        // Never follow or replace an existing file
+        let mut file = private_file_options().open(&path)?;
+        file.write_all(&bytes)?;
+        file.sync_all()?;
+        log::debug!("Spooled recording to {}", path.display());
+
+        Ok(Self { path })
+    }
+
+    /// Look up a recording in the spool directory by its path
+    ///
+    /// Paths outside the directory are rejected, so callers cannot read or
+    /// delete arbitrary files through a recording.
+    pub fn find(path: &Path) -> Result<Self> {
+        Self::find_in(&spool_dir()?, path)
+    }
+
+    fn find_in(dir: &Path, path: &Path) -> Result<Self> {
+        let name = path
+            .file_name()
+            .filter(|_| path.parent() == Some(dir))
+            .ok_or_else(|| {
+                AppError::InvalidState(format!("Not a spooled recording: {}", path.display()))
+            })?;
+
+        let path = dir.join(name);
+        if !path.is_file() {
+            return Err(AppError::InvalidState(format!(
+                "Recording no longer exists: {}",
+                path.display()
+            )));
+        }
+        Ok(Self { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        is_encrypted_path(&self.path)
+    }
+
+    /// Read the recording back, decrypting it with `key` if needed
+    pub fn read(&self, key: &SessionKey) -> Result<AudioData> {
+        let bytes = std::fs::read(&self.path)?;
+        let wav = if self.is_encrypted() {
+            key.open(&bytes).map_err(|_| {
+                AppError::AudioDecoding(
+                    "The recording was encrypted in an earlier session and cannot be read"
+                        .to_string(),
+                )
+            })?
+        } else {
+            bytes
+        };
+
+        decode::decode_bytes(wav, "wav")
+    }
+
+    /// Read the recording back ready for transcription
+    ///
+    /// Legacy files hold raw device audio and are prepared with `config`;
+    /// everything else was prepared before it was spooled.
+    pub fn read_prepared(&self, key: &SessionKey, config: &AudioConfig) -> Result<AudioData> {
+        let audio = self.read(key)?;
+        if self.is_legacy() {
+            prepare_audio(&audio, config)
+        } else {
+            Ok(audio)
+        }
+    }
+
+    fn is_legacy(&self) -> bool {
+        self.path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.starts_with(LEGACY_PREFIX))
+    }
+
+    /// Delete the file, overwriting it first in paranoid mode
+    pub async fn remove(self, paranoid: bool) -> Result<()> {
+        if paranoid {
+            utils::secure_delete_file(&self.path).await
+        } else {
+            tokio::fs::remove_file(&self.path).await?;
+            Ok(())
+        }
+    }
+}
+
+/// Private directory for spooled recordings, created on first use
+///
+/// Lives in the per-user runtime dir, or the user's cache dir where there is
+/// none, so other users cannot claim it first. On Unix only the current user
+/// can list or open it (mode 0700).
+pub fn spool_dir() -> Result<PathBuf> {
+    let dir = dirs::runtime_dir()
+        .or_else(dirs::cache_dir)
+        .ok_or_else(|| AppError::NotFound("No cache directory available".to_string()))?
+        .join("open-whisperflow")
+        .join(SPOOL_DIR_NAME);
+    create_private_dir(&dir)?;
+    Ok(dir)
+}
+
+/// Recordings left in the spool directory that were never processed
+pub fn recoverable() -> Result<Vec<SpooledRecording>> {
+    recoverable_in(&spool_dir()?)
+}
+
+fn recoverable_in(dir: &Path) -> Result<Vec<SpooledRecording>> {
+    let mut recordings: Vec<SpooledRecording> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .map(|path| SpooledRecording { path })
+        .collect();
+
+    recordings.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(recordings)
+}
+
+/// Clean up recordings left behind by earlier runs
+///
+/// Encrypted recordings are deleted, since the key that could read them is
+/// gone. Plain ones are kept for recovery, and legacy files from the shared
+/// temp dir are moved into the private directory. Returns the recordings
+/// that can be recovered.
+pub fn sweep_stale() -> Result<Vec<SpooledRecording>> {
+    sweep_stale_in(&spool_dir()?, &std::env::temp_dir())
+}
+
+fn sweep_stale_in(dir: &Path, legacy_dir: &Path) -> Result<Vec<SpooledRecording>> {
+    if let Ok(entries) = std::fs::read_dir(legacy_dir) {
+        for path in entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+        {
+            let is_legacy = path.is_file()
+                && path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with(LEGACY_PREFIX));
+            if !is_legacy {
+                continue;
+            }
+
+            let moved = dir.join(path.file_name().unwrap_or_default());
+            if let Err(e) = std::fs::rename(&path, &moved) {
+                log::warn!("Failed to move {} into the spool: {}", path.display(), e);
+            }
+        }
+    }
+
+    let mut kept = Vec::new();
+    for recording in recoverable_in(dir)? {
+        if recording.is_encrypted() {
+            log::info!("Deleting unreadable recording {}", recording.path.display());
+            if let Err(e) = std::fs::remove_file(&recording.path) {
+                log::warn!("Failed to delete {}: {}", recording.path.display(), e);
+            }
+        } else {
+            kept.push(recording);
+        }
+    }
+
+    Ok(kept)
+}
+
+fn unique_name(encrypted: bool) -> String {
+    let name = format!(
+        "recording_{}_{}_{}.wav",
+        chrono::Utc::now().timestamp_millis(),
+        std::process::id(),
+        SEQUENCE.fetch_add(1, Ordering::Relaxed)
+    );
+
+    if encrypted {
+        format!("{}.{}", name, ENCRYPTED_EXTENSION)
+    } else {
+        name
+    }
+}
+
+fn is_encrypted_path(path: &Path) -> bool {
+    path.extension().is_some_and(|e| e == ENCRYPTED_EXTENSION)
+}
+
+fn create_private_dir(dir: &Path) -> Result<()> {
+    let mut builder = DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+        use std::os::unix::fs::MetadataExt;
+        builder.mode(0o700);
+        builder.create(dir)?;
+
+        // Never spool into a directory someone else controls
+        let metadata = std::fs::symlink_metadata(dir)?;
+        // SAFETY: geteuid has no preconditions and cannot fail
+        let user = unsafe { libc::geteuid() };
+        if !metadata.is_dir() || metadata.uid() != user {
+            return Err(AppError::InvalidState(format!(
+                "Spool directory {} is not a directory owned by the current user",
+                dir.display()
+            )));
+        }
+
+        // The directory may predate this version, or have been loosened since
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))?;
+    }
+    #[cfg(not(unix))]
+    builder.create(dir)?;
+
+    Ok(())
+}
+
+fn private_file_options() -> OpenOptions {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn speech() -> AudioData {
+        AudioData::new(vec![0.25; 1600], 16000, 1)
+    }
+
+    #[tokio::test]
+    async fn test_plain_spool_is_a_wav_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let spool = SpooledRecording::write_in(dir.path(), &speech(), None).unwrap();
+        let path = spool.path().to_path_buf();
+
+        // Plain recordings do not need the key that was current when written
+        let key = SessionKey::generate().unwrap();
+        assert_eq!(&std::fs::read(&path).unwrap()[..4], b"RIFF");
+        assert_eq!(spool.read(&key).unwrap().samples.len(), 1600);
+
+        spool.remove(false).await.unwrap();
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_spool_is_unreadable_without_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = SessionKey::generate().unwrap();
+        let spool = SpooledRecording::write_in(dir.path(), &speech(), Some(&key)).unwrap();
+        let path = spool.path().to_path_buf();
+
+        let contents = std::fs::read(&path).unwrap();
+        assert!(spool.is_encrypted());
+        assert!(hound::WavReader::new(std::io::Cursor::new(&contents)).is_err());
+        assert_eq!(spool.read(&key).unwrap().samples.len(), 1600);
+        assert!(spool.read(&SessionKey::generate().unwrap()).is_err());
+
+        spool.remove(true).await.unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_recordings_in_quick_succession_get_unique_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = SpooledRecording::write_in(dir.path(), &speech(), None).unwrap();
+        let second = SpooledRecording::write_in(dir.path(), &speech(), None).unwrap();
+
+        assert_ne!(first.path(), second.path());
+        assert_eq!(recoverable_in(dir.path()).unwrap().len(), 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_spool_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join(SPOOL_DIR_NAME);
+        create_private_dir(&dir).unwrap();
+        let spool = SpooledRecording::write_in(&dir, &speech(), None).unwrap();
+
+        let mode = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(&dir), 0o700);
+        assert_eq!(mode(spool.path()), 0o600);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_spool_dir_must_not_be_a_symlink() {
+        let temp = tempfile::tempdir().unwrap();
+        let target = temp.path().join("elsewhere");
+        std::fs::create_dir(&target).unwrap();
+        let dir = temp.path().join(SPOOL_DIR_NAME);
+        std::os::unix::fs::symlink(&target, &dir).unwrap();
+
+        assert!(matches!(create_private_dir(&dir), Err(AppError::InvalidState(_))));
+    }
+
+    #[test]
+    fn test_sweep_keeps_plain_and_deletes_encrypted_recordings() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join(SPOOL_DIR_NAME);
+        create_private_dir(&dir).unwrap();
+
+        let key = SessionKey::generate().unwrap();
+        let plain = SpooledRecording::write_in(&dir, &speech(), None).unwrap();
+        let encrypted = SpooledRecording::write_in(&dir, &speech(), Some(&key)).unwrap();
+        let legacy = temp.path().join("openwhisperflow_1700000000.wav");
+        speech().save_wav(&legacy).unwrap();
+
+        let kept = sweep_stale_in(&dir, temp.path()).unwrap();
+
+        let kept: Vec<_> = kept.iter().map(|r| r.path().to_path_buf()).collect();
+        assert_eq!(kept.len(), 2);
+        assert!(kept.contains(&plain.path().to_path_buf()));
+        assert!(kept.contains(&dir.join("openwhisperflow_1700000000.wav")));
+        assert!(!encrypted.path().exists());
+        assert!(!legacy.exists());
+    }
+
+    #[test]
+    fn test_recovered_recording_is_not_prepared_twice() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = SessionKey::generate().unwrap();
+        let config = AudioConfig::default();
+
+        // A quiet tone after a pause, as the recorder would capture it
+        let raw: Vec<f32> = std::iter::repeat_n(0.0, 48000)
+            .chain((0..96000).map(|i| (i as f32 * 0.06).sin() * 0.1))
+            .flat_map(|s| [s, s])
+            .collect();
+        let raw = AudioData::new(raw, 48000, 2);
+        let prepared = prepare_audio(&raw, &config).unwrap();
+
+        let spool = SpooledRecording::write_in(dir.path(), &prepared, Some(&key)).unwrap();
+        let recovered = spool.read_prepared(&key, &config).unwrap();
+        assert_eq!(recovered.samples.len(), prepared.samples.len());
+        assert!(recovered
+            .samples
+            .iter()
+            .zip(&prepared.samples)
+            .all(|(a, b)| (a - b).abs() < 1e-3));
+
+        // Files from older versions hold the raw capture
+        let legacy = dir.path().join("openwhisperflow_1700000000.wav");
+        raw.save_wav(&legacy).unwrap();
+        let recovered = SpooledRecording::find_in(dir.path(), &legacy)
+            .unwrap()
+            .read_prepared(&key, &config)
+            .unwrap();
+        assert_eq!((recovered.sample_rate, recovered.channels), (16000, 1));
+        assert_eq!(recovered.samples.len(), prepared.samples.len());
+    }
+
+    #[test]
+    fn test_find_rejects_paths_outside_spool() {
+        let dir = tempfile::tempdir().unwrap();
+        let spool = SpooledRecording::write_in(dir.path(), &speech(), None).unwrap();
+
+        assert!(SpooledRecording::find_in(dir.path(), spool.path()).is_ok());
+        assert!(SpooledRecording::find_in(dir.path(), Path::new("/etc/passwd")).is_err());
+        assert!(SpooledRecording::find_in(dir.path(), &dir.path().join("../x.wav")).is_err());
+    }
+}
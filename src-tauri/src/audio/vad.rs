@@ -0,0 +1,305 @@
+use crate::config::AudioConfig;
+
+/// Energy that is always treated as silence, in dBFS
+const SILENCE_FLOOR_DB: f32 = -60.0;
+/// Upper bound for the adaptive threshold so continuous speech is never dropped
+const MAX_THRESHOLD_DB: f32 = -35.0;
+/// Speech padding kept around every voiced region so word edges are not clipped
+const SPEECH_PADDING_MS: u32 = 150;
+/// Speech required before trailing silence can end a recording
+const MIN_SPEECH_MS: u32 = 200;
+/// How fast the streaming noise floor estimate may rise, per frame
+const NOISE_FLOOR_RISE_DB: f32 = 0.02;
+
+/// Margin speech must rise above the noise floor for an aggressiveness level
+fn margin_db(aggressiveness: u8) -> f32 {
+    match aggressiveness.min(3) {
+        0 => 6.0,
+        1 => 9.0,
+        2 => 12.0,
+        _ => 15.0,
+    }
+}
+
+/// Frame-based, energy-driven voice activity detector
+///
+/// The threshold adapts to the recording: the noise floor is estimated from
+/// the quietest frames and speech must rise a fixed margin above it. Higher
+/// aggressiveness raises the margin and removes more borderline audio.
+#[derive(Debug, Clone)]
+pub struct VoiceActivityDetector {
+    frame_len: usize,
+    margin_db: f32,
+    padding_frames: usize,
+    max_pause_frames: usize,
+}
+
+impl VoiceActivityDetector {
+    pub fn new(sample_rate: u32, frame_duration_ms: u32, aggressiveness: u8, max_pause_ms: u32) -> Self {
+        let frame_duration_ms = frame_duration_ms.clamp(10, 100);
+        let frame_len = ((sample_rate as u64 * frame_duration_ms as u64) / 1000).max(1) as usize;
+
+        Self {
+            frame_len,
+            margin_db: margin_db(aggressiveness),
+            padding_frames: SPEECH_PADDING_MS.div_ceil(frame_duration_ms) as usize,
+            max_pause_frames: (max_pause_ms / frame_duration_ms) as usize,
+        }
+    }
+
+    /// Build a detector for mono audio at `sample_rate` from the audio config
+    pub fn from_config(config: &AudioConfig, sample_rate: u32) -> Self {
+        Self::new(
+            sample_rate,
+            config.vad_frame_duration_ms,
+            config.vad_aggressiveness,
+            config.vad_max_pause_ms,
+        )
+    }
+
+    /// Classify each frame of mono audio as speech (`true`) or silence
+    pub fn speech_frames(&self, samples: &[f32]) -> Vec<bool> {
+        let energies: Vec<f32> = samples.chunks(self.frame_len).map(frame_energy_db).collect();
+        let threshold = self.threshold_db(&energies);
+        let voiced: Vec<bool> = energies.iter().map(|&e| e > threshold).collect();
+
+        // Pad voiced regions so soft onsets and trailing consonants survive
+        let mut padded = voiced.clone();
+        for (i, _) in voiced.iter().enumerate().filter(|(_, &v)| v) {
+            let start = i.saturating_sub(self.padding_frames);
+            let end = (i + self.padding_frames + 1).min(padded.len());
+            padded[start..end].iter_mut().for_each(|f| *f = true);
+        }
+        padded
+    }
+
+    /// Trim leading and trailing silence and shorten long internal pauses
+    ///
+    /// Returns an empty buffer when no speech is detected at all.
+    pub fn process(&self, samples: &[f32]) -> Vec<f32> {
+        samples
+            .iter()
+            .zip(self.keep_mask(samples))
+            .filter_map(|(&sample, keep)| keep.then_some(sample))
+            .collect()
+    }
+
+    /// Per-sample mask of the audio that [`process`](Self::process) keeps
+    pub fn keep_mask(&self, samples: &[f32]) -> Vec<bool> {
+        let frames = self.speech_frames(samples);
+        let mut mask = vec![false; samples.len()];
+
+        let (Some(first), Some(last)) = (
+            frames.iter().position(|&f| f),
+            frames.iter().rposition(|&f| f),
+        ) else {
+            return mask;
+        };
+
+        let mut pause_run = 0;
+        for (index, &is_speech) in frames.iter().enumerate().take(last + 1).skip(first) {
+            if is_speech {
+                pause_run = 0;
+            } else {
+                pause_run += 1;
+                if pause_run > self.max_pause_frames {
+                    continue;
+                }
+            }
+
+            let start = index * self.frame_len;
+            let end = (start + self.frame_len).min(samples.len());
+            mask[start..end].iter_mut().for_each(|keep| *keep = true);
+        }
+
+        mask
+    }
+
+    /// Energy threshold for this recording, from its noise floor
+    fn threshold_db(&self, energies: &[f32]) -> f32 {
+        if energies.is_empty() {
+            return SILENCE_FLOOR_DB;
+        }
+
+        let mut sorted = energies.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let noise_floor = sorted[sorted.len() / 10];
+
+        (noise_floor + self.margin_db).clamp(SILENCE_FLOOR_DB, MAX_THRESHOLD_DB)
+    }
+}
+
+/// Streaming detector for trailing silence after speech
+///
+/// Fed with the live input stream, it tracks the noise floor as it goes and
+/// reports once speech has been heard and then followed by enough silence.
+#[derive(Debug, Clone)]
+pub struct SilenceMonitor {
+    channels: usize,
+    frame_len: usize,
+    margin_db: f32,
+    frame: Vec<f32>,
+    noise_floor_db: Option<f32>,
+    speech_frames: usize,
+    min_speech_frames: usize,
+    silent_frames: usize,
+    stop_after_frames: usize,
+}
+
+impl SilenceMonitor {
+    pub fn new(sample_rate: u32, channels: u16, config: &AudioConfig) -> Self {
+        let frame_duration_ms = config.vad_frame_duration_ms.clamp(10, 100);
+        let frame_len = ((sample_rate as u64 * frame_duration_ms as u64) / 1000).max(1) as usize;
+
+        Self {
+            channels: channels.max(1) as usize,
+            frame_len,
+            margin_db: margin_db(config.vad_aggressiveness),
+            frame: Vec::with_capacity(frame_len),
+            noise_floor_db: None,
+            speech_frames: 0,
+            min_speech_frames: MIN_SPEECH_MS.div_ceil(frame_duration_ms) as usize,
+            silent_frames: 0,
+            stop_after_frames: config.auto_stop_silence_ms.div_ceil(frame_duration_ms) as usize,
+        }
+    }
+
+    /// Feed interleaved samples; returns `true` once trailing silence is long enough
+    pub fn push(&mut self, samples: &[f32]) -> bool {
+        let mut should_stop = false;
+
+        for frame in samples.chunks(self.channels) {
+            self.frame.push(frame.iter().sum::<f32>() / frame.len() as f32);
+
+            if self.frame.len() == self.frame_len {
+                let energy = frame_energy_db(&self.frame);
+                self.frame.clear();
+                should_stop |= self.push_frame_energy(energy);
+            }
+        }
+
+        should_stop
+    }
+
+    fn push_frame_energy(&mut self, energy: f32) -> bool {
+        // Track the floor quickly downwards and slowly upwards
+        let floor = match self.noise_floor_db {
+            Some(floor) if energy >= floor => floor + NOISE_FLOOR_RISE_DB,
+            _ => energy.max(SILENCE_FLOOR_DB - self.margin_db),
+        };
+        self.noise_floor_db = Some(floor);
+
+        let threshold = (floor + self.margin_db).clamp(SILENCE_FLOOR_DB, MAX_THRESHOLD_DB);
+        if energy > threshold {
+            self.speech_frames += 1;
+            self.silent_frames = 0;
+        } else {
+            self.silent_frames += 1;
+        }
+
+        self.speech_frames >= self.min_speech_frames && self.silent_frames >= self.stop_after_frames
+    }
+}
+
+/// RMS energy of a frame in dBFS
+pub fn frame_energy_db(frame: &[f32]) -> f32 {
+    if frame.is_empty() {
+        return f32::NEG_INFINITY;
+    }
+    let mean_square = frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32;
+    10.0 * mean_square.max(1e-12).log10()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16000;
+
+    fn tone(ms: u32) -> Vec<f32> {
+        let len = (RATE * ms / 1000) as usize;
+        (0..len)
+            .map(|i| (2.0 * std::f32::consts::PI * 220.0 * i as f32 / RATE as f32).sin() * 0.3)
+            .collect()
+    }
+
+    fn silence(ms: u32) -> Vec<f32> {
+        // Low-level noise rather than digital silence, like a real mic
+        (0..(RATE * ms / 1000) as usize)
+            .map(|i| if i % 2 == 0 { 0.0005 } else { -0.0005 })
+            .collect()
+    }
+
+    #[test]
+    fn test_trims_leading_and_trailing_silence() {
+        let vad = VoiceActivityDetector::new(RATE, 30, 1, 1000);
+        let samples = [silence(2000), tone(1000), silence(2000)].concat();
+
+        let output = vad.process(&samples);
+
+        // One second of speech plus padding on either side
+        let max_len = (RATE as usize) + 2 * (RATE as usize * 200 / 1000);
+        assert!(output.len() >= RATE as usize);
+        assert!(output.len() <= max_len, "{} > {}", output.len(), max_len);
+    }
+
+    #[test]
+    fn test_collapses_long_pauses() {
+        let vad = VoiceActivityDetector::new(RATE, 30, 1, 500);
+        let samples = [tone(500), silence(3000), tone(500)].concat();
+
+        let output = vad.process(&samples);
+
+        // The 3 s pause is collapsed to roughly 500 ms
+        assert!(output.len() < (RATE as usize * 2));
+        assert!(output.len() > RATE as usize);
+    }
+
+    #[test]
+    fn test_only_silence_returns_empty() {
+        let vad = VoiceActivityDetector::new(RATE, 30, 1, 1000);
+        assert!(vad.process(&silence(2000)).is_empty());
+        assert!(vad.process(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_continuous_speech_is_kept() {
+        let vad = VoiceActivityDetector::new(RATE, 30, 3, 1000);
+        let samples = tone(2000);
+
+        assert_eq!(vad.process(&samples).len(), samples.len());
+    }
+
+    #[test]
+    fn test_silence_monitor_stops_after_speech_then_silence() {
+        let config = AudioConfig {
+            auto_stop_silence_ms: 1000,
+            ..AudioConfig::default()
+        };
+        let mut monitor = SilenceMonitor::new(RATE, 1, &config);
+
+        assert!(!monitor.push(&silence(500)));
+        assert!(!monitor.push(&tone(800)));
+        assert!(!monitor.push(&silence(700)));
+        assert!(monitor.push(&silence(400)));
+    }
+
+    #[test]
+    fn test_silence_monitor_ignores_silence_without_speech() {
+        let config = AudioConfig {
+            auto_stop_silence_ms: 500,
+            ..AudioConfig::default()
+        };
+        let mut monitor = SilenceMonitor::new(RATE, 2, &config);
+
+        let stereo_silence: Vec<f32> = silence(3000).iter().flat_map(|&s| [s, s]).collect();
+        assert!(!monitor.push(&stereo_silence));
+    }
+
+    #[test]
+    fn test_frame_energy() {
+        assert!(frame_energy_db(&[0.0; 160]) < SILENCE_FLOOR_DB);
+        let full_scale = frame_energy_db(&[1.0; 160]);
+        assert!(full_scale.abs() < 0.01);
+    }
+}
@@ -1,40 +1,37 @@
-use tauri::State;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
 
-use crate::audio::AudioRecorder;
+use crate::audio::spool::SpooledRecording;
+use crate::audio::{AudioData, AudioDeviceInfo, AutoStopReason, RecorderEvent, RecorderEvents};
 use crate::config::AppConfig;
 use crate::docker::DockerClient;
 use crate::error::{AppError, Result};
 use crate::injection::TextInjector;
 use crate::llm::LLMService;
 use crate::state::{AppState, ProcessingStage, RecordingState};
-use crate::transcription::TranscriptionService;
+use crate::transcription::{Segment, Transcript, TranscriptionService};
 
 /// Start audio recording
 #[tauri::command]
-pub async fn start_recording(state: State<'_, AppState>) -> Result<()> {
+pub async fn start_recording(app: AppHandle, state: State<'_, AppState>) -> Result<()> {
     log::info!("Command: start_recording");
 
     // Check if already recording
-    if state.is_recording().await {
+    if state.has_active_recording().await {
         return Err(crate::error::AppError::InvalidState(
             "Already recording".to_string(),
         ));
     }
 
-    // Create audio recorder if it doesn't exist and start recording
-    {
-        let mut recorder_opt = state.audio_recorder.lock().unwrap();
-        if recorder_opt.is_none() {
-            *recorder_opt = Some(AudioRecorder::new()?);
-        }
+    let audio_config = state.config.read().await.audio.clone();
 
-        // Start recording before releasing lock
-        if let Some(ref mut recorder) = *recorder_opt {
-            recorder.start_recording()?;
-        }
-    } // Lock is dropped here
+    // Create audio recorder if it doesn't exist (or the device changed) and start recording
+    state.recorder.configure(&audio_config).await?;
+    let events = state.recorder.start().await?;
+
+    tauri::async_runtime::spawn(handle_recorder_events(app, events));
 
-    // Update state after releasing lock
     state
         .set_recording_state(RecordingState::Recording {
             started_at: chrono::Utc::now().timestamp_millis() as u64,
@@ -44,23 +41,256 @@ pub async fn start_recording(state: State<'_, AppState>) -> Result<()> {
     Ok(())
 }
 
+/// Payload of the `recording-auto-stopped` event
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AutoStopEvent {
+    pub reason: AutoStopReason,
+}
+
+/// Payload of the `recording-time-limit-approaching` event
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TimeLimitEvent {
+    pub remaining_seconds: u64,
+    pub max_seconds: u64,
+}
+
+/// A running recording that delivers no audio for this long has lost its device
+///
+/// Level readings arrive every few dozen milliseconds while audio flows.
+const STREAM_STALL_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// React to events raised by the recorder for the current recording
+///
+/// An auto-stop runs the same pipeline as `stop_recording`; the outcome is
+/// reported through `recording-processed` or `recording-failed` events since
+/// there is no command invocation waiting for it.
+async fn handle_recorder_events(app: AppHandle, mut events: RecorderEvents) {
+    // Set once a finite input such as a file has been played back
+    let mut input_ended = false;
+    loop {
+        let event = match tokio::time::timeout(STREAM_STALL_TIMEOUT, events.recv()).await {
+            Ok(Some(event)) => event,
+            Ok(None) => break,
+            Err(_) => {
+                // A paused recording or a finished file is expected to be quiet
+                if input_ended || !app.state::<AppState>().is_recording().await {
+                    continue;
+                }
+                RecorderEvent::StreamFailed("The input device stopped delivering audio".to_string())
+            }
+        };
+
+        match event {
+            RecorderEvent::AutoStop(reason) => {
+                let state = app.state::<AppState>();
+                if !state.is_recording().await {
+                    continue;
+                }
+
+                log::info!("Recording auto-stopped: {:?}", reason);
+                app.emit("recording-auto-stopped", AutoStopEvent { reason }).ok();
+
+                match process_recording(&state).await {
+                    Ok(Some(result)) => {
+                        app.emit("recording-processed", result).ok();
+                    }
+                    // Stopped by the user in the meantime
+                    Ok(None) => {}
+                    Err(e) => {
+                        log::error!("Processing auto-stopped recording failed: {}", e);
+                        let message = e.user_message();
+                        state
+                            .set_recording_state(RecordingState::Error {
+                                message: message.clone(),
+                            })
+                            .await;
+                        app.emit("recording-failed", message).ok();
+                    }
+                }
+                break;
+            }
+            RecorderEvent::TimeLimitApproaching {
+                remaining_seconds,
+                max_seconds,
+            } => {
+                log::info!("Recording time limit in {}s", remaining_seconds);
+                app.emit(
+                    "recording-time-limit-approaching",
+                    TimeLimitEvent {
+                        remaining_seconds,
+                        max_seconds,
+                    },
+                )
+                .ok();
+            }
+            RecorderEvent::Level(level) => {
+                app.emit("audio-level", level).ok();
+            }
+            RecorderEvent::InputEnded => {
+                log::info!("Input file played back; waiting for the recording to be stopped");
+                input_ended = true;
+            }
+            RecorderEvent::StreamFailed(reason) => {
+                let state = app.state::<AppState>();
+                if !state.has_active_recording().await {
+                    continue;
+                }
+
+                log::error!("Input stream failed during recording: {}", reason);
+                if !handle_stream_failure(&app, &state, &reason).await {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Recover from a failed input stream during a recording
+///
+/// Continues on another device when allowed. Otherwise the recording ends in
+/// an error. The audio captured so far is prepared and kept like any other
+/// recording: spooled for recovery when enabled, and archived when archiving
+/// is on. Returns whether the recording is still running.
+async fn handle_stream_failure(app: &AppHandle, state: &AppState, reason: &str) -> bool {
+    let (audio_config, privacy) = {
+        let config = state.config.read().await;
+        (config.audio.clone(), config.privacy.clone())
+    };
+
+    if audio_config.reconnect_on_device_loss {
+        match state.recorder.reconnect().await {
+            Ok(device) => {
+                app.emit("recording-device-changed", device).ok();
+                return true;
+            }
+            Err(e) => log::error!("Failed to reopen audio input: {}", e),
+        }
+    }
+
+    let audio = match state.recorder.abort().await {
+        Ok(audio) => audio,
+        Err(e) => {
+            log::error!("Failed to end interrupted recording: {}", e);
+            None
+        }
+    };
+
+    let mut message =
+        AppError::AudioRecording(format!("Recording interrupted: {}", reason)).user_message();
+    if let Some(audio) = audio {
+        if audio_config.archive_recordings {
+            match crate::audio::archive_recording(&audio, &audio_config.archive_dir) {
+                Ok(path) => log::info!("Interrupted recording archived to: {}", path.display()),
+                Err(e) => log::warn!("Failed to archive interrupted recording: {}", e),
+            }
+        }
+
+        if privacy.keep_until_processed {
+            let key = privacy.audio_encryption.then_some(state.session_key.as_ref());
+            match SpooledRecording::write(&audio, key) {
+                Ok(spool) => {
+                    log::info!("Interrupted recording kept at {}", spool.path().display());
+                    message.push_str(" The audio recorded so far can be recovered.");
+                }
+                Err(e) => log::error!("Failed to spool interrupted recording: {}", e),
+            }
+        }
+    }
+
+    state
+        .set_recording_state(RecordingState::Error {
+            message: message.clone(),
+        })
+        .await;
+    app.emit("recording-failed", message).ok();
+    false
+}
+
 /// Stop recording and process audio
+///
+/// Returns nothing if no recording is running, e.g. because an auto-stop is
+/// already processing it; its result arrives as `recording-processed`.
 #[tauri::command]
-pub async fn stop_recording(state: State<'_, AppState>) -> Result<ProcessedResult> {
+pub async fn stop_recording(state: State<'_, AppState>) -> Result<Option<ProcessedResult>> {
     log::info!("Command: stop_recording");
+    process_recording(&state).await
+}
 
-    // Stop recording and get audio file path
-    let audio_path = {
-        let mut recorder_opt = state.audio_recorder.lock().unwrap();
-        if let Some(ref mut recorder) = *recorder_opt {
-            recorder.stop_recording()?
-        } else {
-            return Err(AppError::InvalidState("No active recorder".to_string()));
+/// Stop the recorder, then transcribe, rewrite and inject the audio
+///
+/// Returns `None` without touching the state if another caller already
+/// stopped the recording.
+async fn process_recording(state: &AppState) -> Result<Option<ProcessedResult>> {
+    if !state.claim_recording().await {
+        log::info!("No recording to stop");
+        return Ok(None);
+    }
+
+    // Stop recording and take the audio
+    let audio = match state.recorder.stop().await {
+        Ok(audio) => audio,
+        Err(e) => {
+            // The recorder has ended the recording either way
+            state
+                .set_recording_state(RecordingState::Error {
+                    message: e.user_message(),
+                })
+                .await;
+            return Err(e);
         }
     };
 
-    log::info!("Audio saved to: {}", audio_path.display());
+    // An accidental, empty or silent dictation has nothing to transcribe
+    if audio.is_empty() {
+        state.set_recording_state(RecordingState::Idle).await;
+        return Ok(Some(ProcessedResult::default()));
+    }
 
+    let (audio_config, privacy) = {
+        let config = state.config.read().await;
+        (config.audio.clone(), config.privacy.clone())
+    };
+    if audio_config.archive_recordings {
+        match crate::audio::archive_recording(&audio, &audio_config.archive_dir) {
+            Ok(path) => log::info!("Recording archived to: {}", path.display()),
+            Err(e) => log::warn!("Failed to archive recording: {}", e),
+        }
+    }
+
+    // Audio stays in memory unless spooling is enabled
+    let spool = if privacy.keep_until_processed {
+        let key = privacy.audio_encryption.then_some(state.session_key.as_ref());
+        match SpooledRecording::write(&audio, key) {
+            Ok(spool) => Some(spool),
+            Err(e) => {
+                log::warn!("Failed to spool recording: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let result = transcribe_and_inject(state, &audio).await;
+
+    if let Some(spool) = spool {
+        if result.is_ok() {
+            if let Err(e) = spool.remove(privacy.paranoid_mode).await {
+                log::warn!("Failed to delete spooled recording: {}", e);
+            }
+        } else {
+            log::warn!(
+                "Processing failed; recording kept at {}",
+                spool.path().display()
+            );
+        }
+    }
+
+    result.map(Some)
+}
+
+/// Transcribe, rewrite and inject a finished recording
+async fn transcribe_and_inject(state: &AppState, audio: &AudioData) -> Result<ProcessedResult> {
     // Transcribe
     state
         .set_recording_state(RecordingState::Processing {
@@ -69,8 +299,14 @@ pub async fn stop_recording(state: State<'_, AppState>) -> Result<ProcessedResul
         .await;
 
     let config = state.config.read().await;
-    let transcription_service = TranscriptionService::new(&config.transcription).await?;
-    let transcription = transcription_service.transcribe(&audio_path).await?;
+    let transcription_service =
+        TranscriptionService::new(&config.transcription, &state.whisper_cpp_models).await?;
+    let Transcript {
+        text: transcription,
+        language,
+        segments,
+        ..
+    } = transcription_service.transcribe(audio).await?;
     log::info!("Transcription: {}", transcription);
 
     // Rewrite with LLM
@@ -97,38 +333,181 @@ pub async fn stop_recording(state: State<'_, AppState>) -> Result<ProcessedResul
     text_injector.inject_text(&cleaned_text).await?;
     log::info!("Text injected successfully");
 
-    // Cleanup
-    crate::utils::secure_delete_file(&audio_path).await.ok();
-
     // Reset state
     state.set_recording_state(RecordingState::Idle).await;
 
     Ok(ProcessedResult {
         transcription,
         cleaned_text,
+        language,
+        segments,
     })
 }
 
+/// Transcribe an audio file from disk, optionally rewriting it with the LLM
+///
+/// Unlike a recording, the result is returned but never injected.
+#[tauri::command]
+pub async fn transcribe_file(
+    state: State<'_, AppState>,
+    path: String,
+    rewrite: bool,
+) -> Result<ProcessedResult> {
+    log::info!("Command: transcribe_file ({})", path);
+
+    let config = state.config.read().await.clone();
+
+    let transcription_service =
+        TranscriptionService::new(&config.transcription, &state.whisper_cpp_models).await?;
+    let Transcript {
+        text: transcription,
+        language,
+        segments,
+        ..
+    } = transcription_service
+        .transcribe_file(std::path::Path::new(&path), &config.audio)
+        .await?;
+    log::info!("Transcription: {}", transcription);
+
+    let cleaned_text = if rewrite {
+        LLMService::new(&config.llm)?.rewrite_text(&transcription).await?
+    } else {
+        transcription.clone()
+    };
+
+    Ok(ProcessedResult {
+        transcription,
+        cleaned_text,
+        language,
+        segments,
+    })
+}
+
+/// List recordings that were spooled but never processed, e.g. after a crash
+#[tauri::command]
+pub async fn list_recoverable_recordings() -> Result<Vec<RecoverableRecording>> {
+    log::info!("Command: list_recoverable_recordings");
+
+    Ok(crate::audio::spool::recoverable()?
+        .iter()
+        .map(RecoverableRecording::from)
+        .collect())
+}
+
+/// Transcribe a recording left over from a failed run, then delete it
+///
+/// Like [`transcribe_file`], the result is returned but never injected.
+#[tauri::command]
+pub async fn recover_recording(
+    state: State<'_, AppState>,
+    path: String,
+    rewrite: bool,
+) -> Result<ProcessedResult> {
+    log::info!("Command: recover_recording ({})", path);
+
+    let config = state.config.read().await.clone();
+    let recording = SpooledRecording::find(std::path::Path::new(&path))?;
+
+    let key = Arc::clone(&state.session_key);
+    let audio_config = config.audio.clone();
+    let (recording, audio) = tokio::task::spawn_blocking(move || {
+        let audio = recording.read_prepared(&key, &audio_config)?;
+        Ok::<_, AppError>((recording, audio))
+    })
+    .await
+    .map_err(|e| AppError::Unknown(format!("Decoding task failed: {}", e)))??;
+
+    let Transcript {
+        text: transcription,
+        language,
+        segments,
+        ..
+    } = TranscriptionService::new(&config.transcription, &state.whisper_cpp_models)
+        .await?
+        .transcribe(&audio)
+        .await?;
+    log::info!("Recovered transcription: {}", transcription);
+
+    let cleaned_text = if rewrite {
+        LLMService::new(&config.llm)?.rewrite_text(&transcription).await?
+    } else {
+        transcription.clone()
+    };
+
+    if let Err(e) = recording.remove(config.privacy.paranoid_mode).await {
+        log::warn!("Failed to delete recovered recording: {}", e);
+    }
+
+    Ok(ProcessedResult {
+        transcription,
+        cleaned_text,
+        language,
+        segments,
+    })
+}
+
+/// Delete a recording left over from a failed run without transcribing it
+#[tauri::command]
+pub async fn discard_recording(state: State<'_, AppState>, path: String) -> Result<()> {
+    log::info!("Command: discard_recording ({})", path);
+
+    let paranoid = state.config.read().await.privacy.paranoid_mode;
+    SpooledRecording::find(std::path::Path::new(&path))?
+        .remove(paranoid)
+        .await
+}
+
+/// Pause the current recording, keeping the audio captured so far
+#[tauri::command]
+pub async fn pause_recording(state: State<'_, AppState>) -> Result<()> {
+    log::info!("Command: pause_recording");
+
+    let RecordingState::Recording { started_at } = state.get_recording_state().await else {
+        return Err(AppError::InvalidState("Not recording".to_string()));
+    };
+
+    state.recorder.pause().await?;
+
+    let now = chrono::Utc::now().timestamp_millis() as u64;
+    state
+        .set_recording_state(RecordingState::Paused {
+            elapsed_ms: now.saturating_sub(started_at),
+        })
+        .await;
+
+    Ok(())
+}
+
+/// Resume a paused recording
+#[tauri::command]
+pub async fn resume_recording(state: State<'_, AppState>) -> Result<()> {
+    log::info!("Command: resume_recording");
+
+    let RecordingState::Paused { elapsed_ms } = state.get_recording_state().await else {
+        return Err(AppError::InvalidState("Recording is not paused".to_string()));
+    };
+
+    state.recorder.resume().await?;
+
+    // Shift the start so elapsed time excludes the pause
+    let now = chrono::Utc::now().timestamp_millis() as u64;
+    state
+        .set_recording_state(RecordingState::Recording {
+            started_at: now.saturating_sub(elapsed_ms),
+        })
+        .await;
+
+    Ok(())
+}
+
 /// Cancel current recording
 #[tauri::command]
 pub async fn cancel_recording(state: State<'_, AppState>) -> Result<()> {
     log::info!("Command: cancel_recording");
 
-    // Stop recording if active and get audio path
-    let audio_path_opt = if state.is_recording().await {
-        let mut recorder_opt = state.audio_recorder.lock().unwrap();
-        if let Some(ref mut recorder) = *recorder_opt {
-            Some(recorder.stop_recording()?)
-        } else {
-            None
-        }
-    } else {
-        None
-    }; // Lock is dropped here
-
-    // Delete the audio file if exists (after lock is released)
-    if let Some(audio_path) = audio_path_opt {
-        crate::utils::secure_delete_file(&audio_path).await.ok();
+    // Stop recording if active and discard the audio
+    if state.has_active_recording().await {
+        state.recorder.cancel().await?;
     }
 
     // Reset state
@@ -137,6 +516,12 @@ pub async fn cancel_recording(state: State<'_, AppState>) -> Result<()> {
     Ok(())
 }
 
+/// List available audio input devices and their supported configs
+#[tauri::command]
+pub fn list_audio_devices() -> Result<Vec<AudioDeviceInfo>> {
+    crate::audio::list_input_devices()
+}
+
 /// Get current recording state
 #[tauri::command]
 pub async fn get_recording_state(state: State<'_, AppState>) -> Result<RecordingState> {
@@ -157,6 +542,13 @@ pub async fn update_config(state: State<'_, AppState>, config: AppConfig) -> Res
     // Save to disk
     config.save()?;
 
+    // Open or close the warm input stream right away if pre-roll changed
+    if !state.has_active_recording().await {
+        if let Err(e) = state.recorder.configure(&config.audio).await {
+            log::warn!("Failed to apply audio settings: {}", e);
+        }
+    }
+
     // Update in-memory state
     *state.config.write().await = config;
 
@@ -174,9 +566,10 @@ pub fn get_system_info() -> String {
 pub async fn check_transcription_backend(state: State<'_, AppState>) -> Result<BackendStatus> {
     let config = state.config.read().await;
 
-    let (available, message) = match TranscriptionService::new(&config.transcription).await {
+    let service = TranscriptionService::new(&config.transcription, &state.whisper_cpp_models).await;
+    let (available, message) = match service {
         Ok(service) => {
-            let is_available = service.transcribe(&std::path::PathBuf::from("")).await.is_ok();
+            let is_available = service.is_available().await;
             let msg = match config.transcription.backend {
                 crate::config::TranscriptionBackend::OpenAI => {
                     if config.transcription.openai_api_key.is_some() {
@@ -188,8 +581,27 @@ pub async fn check_transcription_backend(state: State<'_, AppState>) -> Result<B
                 crate::config::TranscriptionBackend::FasterWhisper => {
                     "Docker container ready".to_string()
                 }
+                crate::config::TranscriptionBackend::OpenAICompatible => {
+                    if is_available {
+                        "Server reachable".to_string()
+                    } else {
+                        format!("Cannot reach {}", config.transcription.compatible_base_url)
+                    }
+                }
+                crate::config::TranscriptionBackend::WhisperCpp => {
+                    if is_available {
+                        "whisper.cpp server reachable".to_string()
+                    } else {
+                        format!("Cannot reach {}", config.transcription.whisper_cpp_url)
+                    }
+                }
+            };
+            let available = match config.transcription.backend {
+                crate::config::TranscriptionBackend::OpenAICompatible
+                | crate::config::TranscriptionBackend::WhisperCpp => is_available,
+                _ => is_available || config.transcription.openai_api_key.is_some(),
             };
-            (is_available || config.transcription.openai_api_key.is_some(), msg)
+            (available, msg)
         }
         Err(e) => {
             log::error!("Backend check failed: {}", e);
@@ -454,10 +866,40 @@ pub fn get_recommended_ollama_models() -> Vec<OllamaModelInfo> {
 }
 
 // Response types
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, Clone, Default, serde::Serialize)]
 pub struct ProcessedResult {
     pub transcription: String,
     pub cleaned_text: String,
+    /// Language the transcription backend detected, if it reported one
+    pub language: Option<String>,
+    /// Timed segments of the transcription, empty if the backend gives none
+    pub segments: Vec<Segment>,
+}
+
+/// A spooled recording that processing never finished
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RecoverableRecording {
+    pub path: String,
+    /// Unix timestamp in milliseconds
+    pub recorded_at: u64,
+    /// Only readable in the session that recorded it
+    pub encrypted: bool,
+}
+
+impl From<&SpooledRecording> for RecoverableRecording {
+    fn from(recording: &SpooledRecording) -> Self {
+        let recorded_at = std::fs::metadata(recording.path())
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_millis() as u64);
+
+        Self {
+            path: recording.path().display().to_string(),
+            recorded_at,
+            encrypted: recording.is_encrypted(),
+        }
+    }
 }
 
 #[derive(Debug, serde::Serialize)]
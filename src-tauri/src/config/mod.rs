@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 use crate::error::{AppError, Result};
@@ -12,16 +13,81 @@ pub struct AppConfig {
     pub injection: InjectionConfig,
     pub hotkeys: HotkeyConfig,
     pub ui: UIConfig,
+    #[serde(default)]
+    pub privacy: PrivacyConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AudioConfig {
     pub sample_rate: u32,
     pub channels: u16,
     pub bit_depth: u16,
+    /// Input device name, `default`, or `file:<path>` to replay an audio file
     pub device_id: String,
+    /// Record the microphone, system playback, or both mixed together
+    pub capture_mode: CaptureMode,
+    /// Monitor source for system audio, empty to use the first one found
+    pub monitor_device_id: String,
     pub vad_enabled: bool,
+    /// 0-3, higher removes more borderline audio as silence
+    pub vad_aggressiveness: u8,
+    pub vad_frame_duration_ms: u32,
+    /// Internal pauses longer than this are shortened to this length
+    pub vad_max_pause_ms: u32,
+    /// Stop recording automatically once speech is followed by silence
+    pub auto_stop_enabled: bool,
+    pub auto_stop_silence_ms: u32,
+    /// Recordings are stopped and processed at this length (0 = unlimited)
     pub max_recording_duration_seconds: u64,
+    /// Warn this many seconds before the recording limit is reached
+    pub duration_warning_seconds: u64,
+    /// Keep a WAV copy of every recording; audio otherwise stays in memory
+    pub archive_recordings: bool,
+    /// Where archived recordings go, empty for the app data directory
+    pub archive_dir: String,
+    /// Keep the input stream open between recordings and prepend the audio
+    /// captured just before each one, so the first word is not clipped
+    pub pre_roll_enabled: bool,
+    /// Length of the pre-roll buffer in milliseconds
+    pub pre_roll_ms: u32,
+    /// Continue on the default input device if the current one disconnects
+    /// mid-recording; otherwise the recording ends and its audio is saved
+    pub reconnect_on_device_loss: bool,
+    /// Remove rumble and handling noise below `highpass_cutoff_hz`
+    pub highpass_enabled: bool,
+    pub highpass_cutoff_hz: f32,
+    /// Attenuate passages quieter than `noise_gate_threshold_db` (dBFS)
+    pub noise_gate_enabled: bool,
+    pub noise_gate_threshold_db: f32,
+    /// Bring every recording to a consistent level
+    pub normalize_enabled: bool,
+    pub normalize_mode: NormalizeMode,
+    /// Target peak in dBFS for peak normalization
+    pub normalize_peak_db: f32,
+    /// Target integrated loudness in LUFS for loudness normalization
+    pub normalize_lufs: f32,
+    /// Round off peaks near full scale instead of hard clipping
+    pub soft_clip_enabled: bool,
+}
+
+/// Which inputs a recording captures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CaptureMode {
+    Microphone,
+    /// What the system is playing, e.g. a call or video, via a monitor source
+    SystemAudio,
+    /// Microphone and system audio together
+    Mixed,
+}
+
+/// How recordings are normalized
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NormalizeMode {
+    /// Scale so the loudest sample hits the target peak
+    Peak,
+    /// Scale to a target integrated loudness (ITU-R BS.1770)
+    Loudness,
 }
 
 impl Default for AudioConfig {
@@ -31,18 +97,73 @@ impl Default for AudioConfig {
             channels: 1,
             bit_depth: 16,
             device_id: "default".to_string(),
+            capture_mode: CaptureMode::Microphone,
+            monitor_device_id: String::new(),
             vad_enabled: true,
+            vad_aggressiveness: 1,
+            vad_frame_duration_ms: 30,
+            vad_max_pause_ms: 1000,
+            auto_stop_enabled: false,
+            auto_stop_silence_ms: 1500,
             max_recording_duration_seconds: 300,
+            duration_warning_seconds: 30,
+            archive_recordings: false,
+            archive_dir: String::new(),
+            pre_roll_enabled: false,
+            pre_roll_ms: 300,
+            reconnect_on_device_loss: true,
+            highpass_enabled: true,
+            highpass_cutoff_hz: 80.0,
+            noise_gate_enabled: false,
+            noise_gate_threshold_db: -50.0,
+            normalize_enabled: true,
+            normalize_mode: NormalizeMode::Loudness,
+            normalize_peak_db: -1.0,
+            normalize_lufs: -20.0,
+            soft_clip_enabled: true,
         }
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct TranscriptionConfig {
     pub backend: TranscriptionBackend,
     pub model: String,
+    /// Spoken language as an ISO-639-1 code; unset or "auto" detects it
     pub language: Option<String>,
+    pub task: TranscriptionTask,
     pub openai_api_key: Option<String>,
+    /// Audio format uploaded to the OpenAI API
+    pub openai_upload_format: UploadFormat,
+    /// Audio format uploaded to the local faster-whisper server
+    pub faster_whisper_upload_format: UploadFormat,
+    /// Base URL of an OpenAI-compatible server, up to and including `/v1`
+    pub compatible_base_url: String,
+    pub compatible_api_key: Option<String>,
+    /// Header carrying the API key; `Authorization` sends it as a bearer token
+    pub compatible_auth_header: String,
+    pub compatible_timeout_seconds: u64,
+    pub compatible_upload_format: UploadFormat,
+    /// Address of a whisper.cpp `server`
+    pub whisper_cpp_url: String,
+    /// Model file loaded on the whisper.cpp server (empty = keep the current one)
+    pub whisper_cpp_model_path: String,
+    pub whisper_cpp_temperature: f32,
+    /// Longer audio is split at pauses and transcribed in chunks (0 = never split)
+    pub chunk_seconds: u32,
+    /// Audio repeated at the start of each chunk so words at a cut are not lost
+    pub chunk_overlap_ms: u32,
+    /// Chunks sent at once to backends that accept parallel requests
+    pub chunk_concurrency: usize,
+    /// Names and jargon the backend should spell correctly, most important first
+    pub vocabulary: Vec<String>,
+    /// Named vocabulary sets, e.g. per project or team
+    pub vocabulary_profiles: BTreeMap<String, Vec<String>>,
+    /// Profile used on top of `vocabulary` (empty = none)
+    pub active_vocabulary_profile: String,
+    /// Request the timing of each word; slower on some backends
+    pub word_timestamps: bool,
 }
 
 impl Default for TranscriptionConfig {
@@ -51,7 +172,25 @@ impl Default for TranscriptionConfig {
             backend: TranscriptionBackend::OpenAI,
             model: "whisper-1".to_string(),
             language: None,
+            task: TranscriptionTask::Transcribe,
             openai_api_key: None,
+            openai_upload_format: UploadFormat::Flac,
+            faster_whisper_upload_format: UploadFormat::Wav,
+            compatible_base_url: "http://localhost:8000/v1".to_string(),
+            compatible_api_key: None,
+            compatible_auth_header: "Authorization".to_string(),
+            compatible_timeout_seconds: 300,
+            compatible_upload_format: UploadFormat::Flac,
+            whisper_cpp_url: "http://127.0.0.1:8080".to_string(),
+            whisper_cpp_model_path: String::new(),
+            whisper_cpp_temperature: 0.0,
+            chunk_seconds: 60,
+            chunk_overlap_ms: 1000,
+            chunk_concurrency: 3,
+            vocabulary: Vec::new(),
+            vocabulary_profiles: BTreeMap::new(),
+            active_vocabulary_profile: String::new(),
+            word_timestamps: false,
         }
     }
 }
@@ -60,6 +199,31 @@ impl Default for TranscriptionConfig {
 pub enum TranscriptionBackend {
     FasterWhisper,
     OpenAI,
+    /// Any server implementing the OpenAI transcription API
+    OpenAICompatible,
+    /// whisper.cpp's native HTTP server
+    WhisperCpp,
+}
+
+/// What the backend produces from the speech
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TranscriptionTask {
+    /// Text in the spoken language
+    #[default]
+    Transcribe,
+    /// English text, whatever language was spoken
+    Translate,
+}
+
+/// Encoding used when uploading audio to a transcription backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UploadFormat {
+    /// Uncompressed 16-bit PCM
+    Wav,
+    /// Lossless, roughly half the size of WAV
+    Flac,
+    /// Lossy Opus in Ogg, smallest but may cost some accuracy
+    Opus,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -124,6 +288,22 @@ pub struct HotkeyConfig {
     pub cancel_recording: String,
 }
 
+/// How audio that touches the disk is protected
+///
+/// Everything is off by default, so recordings never touch the disk unless
+/// archiving or spooling is turned on.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrivacyConfig {
+    /// Hold each recording in a temp file until it has been processed, so a
+    /// crash during transcription does not lose it
+    pub keep_until_processed: bool,
+    /// Encrypt temporary audio with a key that only exists in memory
+    pub audio_encryption: bool,
+    /// Overwrite temporary audio before deleting it
+    pub paranoid_mode: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UIConfig {
     pub theme: Theme,
@@ -154,6 +334,7 @@ impl Default for AppConfig {
                 show_notifications: true,
                 minimize_to_tray: true,
             },
+            privacy: PrivacyConfig::default(),
         }
     }
 }
@@ -209,6 +390,22 @@ mod tests {
         assert!(config.max_recording_duration_seconds <= 3600);
     }
 
+    #[test]
+    fn test_audio_config_missing_fields_use_defaults() {
+        let json = r#"{
+            "sample_rate": 16000,
+            "channels": 1,
+            "bit_depth": 16,
+            "device_id": "default",
+            "vad_enabled": true,
+            "max_recording_duration_seconds": 300
+        }"#;
+
+        let config: AudioConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.vad_aggressiveness, 1);
+        assert_eq!(config.vad_frame_duration_ms, 30);
+    }
+
     #[test]
     fn test_transcription_config_defaults() {
         let config = TranscriptionConfig::default();
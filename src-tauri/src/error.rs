@@ -6,6 +6,9 @@ pub enum AppError {
     #[error("Audio recording error: {0}")]
     AudioRecording(String),
 
+    #[error("Audio file error: {0}")]
+    AudioDecoding(String),
+
     #[error("Transcription error: {0}")]
     Transcription(String),
 
@@ -57,6 +60,12 @@ impl AppError {
                     self.clean_message(msg)
                 )
             }
+            AppError::AudioDecoding(msg) => {
+                format!(
+                    "Could not read audio file: {}. Supported formats are WAV, FLAC, MP3 and OGG.",
+                    self.clean_message(msg)
+                )
+            }
             AppError::Transcription(msg) => {
                 format!(
                     "Transcription failed: {}. Try using cloud transcription or check Docker status.",
@@ -150,6 +159,7 @@ impl AppError {
     pub fn category(&self) -> &str {
         match self {
             AppError::AudioRecording(_) => "audio",
+            AppError::AudioDecoding(_) => "audio_file",
             AppError::Transcription(_) => "transcription",
             AppError::LLMProcessing(_) => "llm",
             AppError::TextInjection(_) => "injection",
@@ -209,6 +219,16 @@ mod tests {
         assert!(message.contains("microphone permissions"));
     }
 
+    #[test]
+    fn test_audio_decoding_error_message() {
+        let error = AppError::AudioDecoding("unsupported codec".to_string());
+        let message = error.user_message();
+
+        assert!(message.contains("Could not read audio file"));
+        assert!(message.contains("Unsupported codec"));
+        assert!(!message.contains("microphone"));
+    }
+
     #[test]
     fn test_network_error_message() {
         // Create a network error by building a client with invalid config
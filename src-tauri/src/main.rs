@@ -25,6 +25,13 @@ async fn prepare_backends(state: AppState) {
     // Load config
     let config = state.config.read().await;
 
+    // Open the input stream early so pre-roll is available for the first recording
+    if config.audio.pre_roll_enabled {
+        if let Err(e) = state.recorder.configure(&config.audio).await {
+            log::warn!("Failed to prepare audio recorder: {}", e);
+        }
+    }
+
     // If using faster-whisper, pre-start the container
     if matches!(config.transcription.backend, config::TranscriptionBackend::FasterWhisper) {
         log::info!("Detected faster-whisper backend, pre-starting Docker container...");
@@ -61,6 +68,22 @@ async fn prepare_backends(state: AppState) {
         }
     }
 
+    // If using a whisper.cpp server, load the configured model ahead of time
+    if matches!(config.transcription.backend, config::TranscriptionBackend::WhisperCpp) {
+        let options = transcription::RequestOptions::from_config(&config.transcription);
+        match transcription::WhisperCppBackend::new(
+            &config.transcription,
+            options,
+            state.whisper_cpp_models.clone(),
+        ) {
+            Ok(backend) => match backend.prepare().await {
+                Ok(_) => log::info!("whisper.cpp server ready"),
+                Err(e) => log::warn!("whisper.cpp server not ready: {}. Will retry on first use.", e),
+            },
+            Err(e) => log::warn!("Failed to initialize whisper.cpp backend: {}", e),
+        }
+    }
+
     log::info!("Backend preparation complete");
 }
 
@@ -79,6 +102,16 @@ fn main() {
         .setup(|app| {
             log::info!("Setting up application");
 
+            // Clean up recordings from earlier runs; unprocessed ones stay recoverable
+            match audio::spool::sweep_stale() {
+                Ok(recoverable) if !recoverable.is_empty() => log::warn!(
+                    "{} recording(s) from an earlier run were never processed and can be recovered",
+                    recoverable.len()
+                ),
+                Ok(_) => {}
+                Err(e) => log::warn!("Failed to clean up temporary recordings: {}", e),
+            }
+
             // Initialize application state
             let app_state = AppState::new().expect("Failed to create app state");
 
@@ -99,8 +132,15 @@ fn main() {
         .invoke_handler(tauri::generate_handler![
             commands::start_recording,
             commands::stop_recording,
+            commands::pause_recording,
+            commands::resume_recording,
             commands::cancel_recording,
+            commands::transcribe_file,
+            commands::list_recoverable_recordings,
+            commands::recover_recording,
+            commands::discard_recording,
             commands::get_recording_state,
+            commands::list_audio_devices,
             commands::get_config,
             commands::update_config,
             commands::get_system_info,
@@ -1,9 +1,11 @@
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 use tokio::sync::RwLock;
 
-use crate::audio::AudioRecorder;
+use crate::audio::RecorderHandle;
 use crate::config::AppConfig;
 use crate::error::Result;
+use crate::transcription::LoadedModels;
+use crate::utils::crypto::SessionKey;
 
 /// Application state shared across all Tauri commands
 #[derive(Clone)]
@@ -13,14 +15,14 @@ pub struct AppState {
     /// Reserved for future use (streaming audio, real-time processing)
     #[allow(dead_code)]
     pub audio_buffer: Arc<RwLock<Option<Vec<f32>>>>,
-    pub audio_recorder: Arc<Mutex<Option<AudioRecorder>>>,
+    /// Recorder running on its own audio thread
+    pub recorder: RecorderHandle,
+    /// Encrypts temporary audio; regenerated on every launch
+    pub session_key: Arc<SessionKey>,
+    /// Models loaded on whisper.cpp servers during this session
+    pub whisper_cpp_models: LoadedModels,
 }
 
-// SAFETY: AudioRecorder contains cpal::Stream which is !Send, but we ensure single-threaded access via Mutex
-// All access to audio_recorder is synchronized through the Mutex, so this is safe
-unsafe impl Send for AppState {}
-unsafe impl Sync for AppState {}
-
 /// Current state of the recording process
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type", content = "data")]
@@ -29,6 +31,9 @@ pub enum RecordingState {
     Recording {
         started_at: u64, // Unix timestamp in milliseconds
     },
+    Paused {
+        elapsed_ms: u64, // Recorded time before the pause
+    },
     Processing {
         stage: ProcessingStage,
     },
@@ -55,7 +60,9 @@ impl AppState {
             config: Arc::new(RwLock::new(config)),
             recording_state: Arc::new(RwLock::new(RecordingState::Idle)),
             audio_buffer: Arc::new(RwLock::new(None)),
-            audio_recorder: Arc::new(Mutex::new(None)),
+            recorder: RecorderHandle::spawn()?,
+            session_key: Arc::new(SessionKey::generate()?),
+            whisper_cpp_models: LoadedModels::default(),
         })
     }
 
@@ -77,6 +84,34 @@ impl AppState {
         )
     }
 
+    /// Check if a recording is running or paused
+    pub async fn has_active_recording(&self) -> bool {
+        matches!(
+            *self.recording_state.read().await,
+            RecordingState::Recording { .. } | RecordingState::Paused { .. }
+        )
+    }
+
+    /// Move a running or paused recording on to processing
+    ///
+    /// Returns false if there is no recording to stop, e.g. because an
+    /// auto-stop already took it. Only the caller that gets true owns the
+    /// recording from here on.
+    pub async fn claim_recording(&self) -> bool {
+        let mut state = self.recording_state.write().await;
+        if !matches!(
+            *state,
+            RecordingState::Recording { .. } | RecordingState::Paused { .. }
+        ) {
+            return false;
+        }
+
+        *state = RecordingState::Processing {
+            stage: ProcessingStage::Transcribing,
+        };
+        true
+    }
+
     /// Check if currently processing
     /// Reserved for frontend status checking
     #[allow(dead_code)]
@@ -93,3 +128,21 @@ impl Default for AppState {
         Self::new().expect("Failed to create default AppState")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_only_one_caller_claims_a_recording() {
+        let state = AppState::new().unwrap();
+        assert!(!state.claim_recording().await);
+
+        state
+            .set_recording_state(RecordingState::Paused { elapsed_ms: 1000 })
+            .await;
+        assert!(state.claim_recording().await);
+        assert!(!state.claim_recording().await);
+        assert!(state.is_processing().await);
+    }
+}
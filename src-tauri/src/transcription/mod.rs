@@ -1,18 +1,124 @@
+mod prompt;
+mod transcript;
+mod whisper_cpp;
+
+pub use transcript::{Segment, Transcript};
+pub use whisper_cpp::{LoadedModels, WhisperCppBackend};
+
 use async_trait::async_trait;
+use futures_util::{stream, StreamExt, TryStreamExt};
+use reqwest::header::{HeaderName, AUTHORIZATION};
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
 
-use crate::config::TranscriptionConfig;
+use crate::audio::{chunk, encode, AudioData};
+use crate::config::{AudioConfig, TranscriptionConfig, TranscriptionTask, UploadFormat};
 use crate::docker::DockerClient;
 use crate::error::{AppError, Result};
 
 /// Trait for transcription backends
 #[async_trait]
 pub trait TranscriptionBackend: Send + Sync {
-    async fn transcribe(&self, audio_path: &Path) -> Result<String>;
+    async fn transcribe(&self, audio: &AudioData) -> Result<Transcript>;
     async fn is_available(&self) -> bool;
     fn name(&self) -> &str;
+
+    /// Whether several chunks of one recording may be sent at the same time
+    fn supports_concurrent_requests(&self) -> bool {
+        false
+    }
+}
+
+/// Settings sent with every request to an OpenAI-compatible endpoint
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RequestOptions {
+    /// ISO-639-1 code of the spoken language; `None` lets the backend detect it
+    pub language: Option<String>,
+    pub task: TranscriptionTask,
+    /// Vocabulary passed as the `prompt` field to bias spelling
+    pub prompt: Option<String>,
+    /// Ask for the timing of each word as well as each segment
+    pub word_timestamps: bool,
+}
+
+impl RequestOptions {
+    pub fn from_config(config: &TranscriptionConfig) -> Self {
+        let language = config
+            .language
+            .as_deref()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.eq_ignore_ascii_case("auto"))
+            .map(str::to_lowercase);
+
+        Self {
+            language,
+            task: config.task,
+            prompt: prompt::vocabulary_prompt(&prompt::vocabulary_terms(config)),
+            word_timestamps: config.word_timestamps,
+        }
+    }
+
+    /// API path for the task, relative to the `/v1` base
+    fn endpoint(&self) -> &'static str {
+        match self.task {
+            TranscriptionTask::Transcribe => "audio/transcriptions",
+            TranscriptionTask::Translate => "audio/translations",
+        }
+    }
+
+    /// Multipart form for one request
+    fn form(
+        &self,
+        model: &str,
+        audio: &AudioData,
+        format: UploadFormat,
+    ) -> Result<reqwest::multipart::Form> {
+        let response_format = response_format(model);
+        let mut form = reqwest::multipart::Form::new()
+            .text("model", model.to_string())
+            .text("response_format", response_format)
+            .part("file", audio_part(audio, format)?);
+
+        // Segments are only returned by default; asking for words alone would drop them
+        if self.word_timestamps && response_format == "verbose_json" {
+            form = form
+                .text("timestamp_granularities[]", "segment")
+                .text("timestamp_granularities[]", "word");
+        }
+
+        // Translations always come out in English and take no language
+        if self.task == TranscriptionTask::Transcribe {
+            if let Some(language) = &self.language {
+                form = form.text("language", language.clone());
+            }
+        }
+        if let Some(prompt) = &self.prompt {
+            form = form.text("prompt", prompt.clone());
+        }
+
+        Ok(form)
+    }
+}
+
+/// Ask for `verbose_json`, with segments and the detected language, where the model supports it
+fn response_format(model: &str) -> &'static str {
+    // OpenAI's GPT-4o transcription models only return plain `json`
+    if model.starts_with("gpt-4o") {
+        "json"
+    } else {
+        "verbose_json"
+    }
+}
+
+/// Encode audio as the multipart `file` field of an OpenAI-compatible request
+fn audio_part(audio: &AudioData, format: UploadFormat) -> Result<reqwest::multipart::Part> {
+    let encoded = encode::encode(audio, format)?;
+    reqwest::multipart::Part::bytes(encoded.bytes)
+        .file_name(encoded.file_name)
+        .mime_str(encoded.mime_type)
+        .map_err(|e| AppError::Transcription(format!("Failed to create multipart: {}", e)))
 }
 
 /// OpenAI Whisper API backend
@@ -20,46 +126,40 @@ pub struct OpenAIWhisperBackend {
     client: reqwest::Client,
     api_key: String,
     model: String,
+    upload_format: UploadFormat,
+    options: RequestOptions,
 }
 
 impl OpenAIWhisperBackend {
-    pub fn new(api_key: String, model: String) -> Self {
+    pub fn new(
+        api_key: String,
+        model: String,
+        upload_format: UploadFormat,
+        options: RequestOptions,
+    ) -> Self {
         Self {
             client: reqwest::Client::new(),
             api_key,
             model,
+            upload_format,
+            options,
         }
     }
 }
 
 #[async_trait]
 impl TranscriptionBackend for OpenAIWhisperBackend {
-    async fn transcribe(&self, audio_path: &Path) -> Result<String> {
-        log::info!("Transcribing with OpenAI Whisper: {}", audio_path.display());
-
-        let file = tokio::fs::read(audio_path).await?;
-
-        let form = reqwest::multipart::Form::new()
-            .text("model", self.model.clone())
-            .part(
-                "file",
-                reqwest::multipart::Part::bytes(file)
-                    .file_name(
-                        audio_path
-                            .file_name()
-                            .unwrap()
-                            .to_string_lossy()
-                            .to_string(),
-                    )
-                    .mime_str("audio/wav")
-                    .map_err(|e| {
-                        AppError::Transcription(format!("Failed to create multipart: {}", e))
-                    })?,
-            );
+    async fn transcribe(&self, audio: &AudioData) -> Result<Transcript> {
+        log::info!(
+            "Transcribing with OpenAI Whisper: {:.2}s of audio",
+            audio.duration_seconds()
+        );
+
+        let form = self.options.form(&self.model, audio, self.upload_format)?;
 
         let response = self
             .client
-            .post("https://api.openai.com/v1/audio/transcriptions")
+            .post(format!("https://api.openai.com/v1/{}", self.options.endpoint()))
             .bearer_auth(&self.api_key)
             .multipart(form)
             .send()
@@ -74,14 +174,10 @@ impl TranscriptionBackend for OpenAIWhisperBackend {
             )));
         }
 
-        let result: serde_json::Value = response.json().await?;
-        let text = result["text"]
-            .as_str()
-            .ok_or_else(|| AppError::Transcription("No text in response".to_string()))?
-            .to_string();
+        let transcript = Transcript::from_response(response.json().await?)?;
 
-        log::info!("Transcription complete: {} characters", text.len());
-        Ok(text)
+        log::info!("Transcription complete: {} characters", transcript.text.len());
+        Ok(transcript)
     }
 
     async fn is_available(&self) -> bool {
@@ -91,6 +187,10 @@ impl TranscriptionBackend for OpenAIWhisperBackend {
     fn name(&self) -> &str {
         "OpenAI Whisper"
     }
+
+    fn supports_concurrent_requests(&self) -> bool {
+        true
+    }
 }
 
 /// faster-whisper backend using Docker
@@ -99,10 +199,16 @@ pub struct FasterWhisperBackend {
     docker: Arc<Mutex<DockerClient>>,
     model: String,
     base_url: String,
+    upload_format: UploadFormat,
+    options: RequestOptions,
 }
 
 impl FasterWhisperBackend {
-    pub async fn new(model: String) -> Result<Self> {
+    pub async fn new(
+        model: String,
+        upload_format: UploadFormat,
+        options: RequestOptions,
+    ) -> Result<Self> {
         let docker = DockerClient::new()?;
 
         // Check if Docker is available
@@ -120,6 +226,8 @@ impl FasterWhisperBackend {
             docker,
             model,
             base_url,
+            upload_format,
+            options,
         })
     }
 
@@ -139,39 +247,20 @@ impl FasterWhisperBackend {
 
 #[async_trait]
 impl TranscriptionBackend for FasterWhisperBackend {
-    async fn transcribe(&self, audio_path: &Path) -> Result<String> {
+    async fn transcribe(&self, audio: &AudioData) -> Result<Transcript> {
         log::info!(
-            "Transcribing with faster-whisper (Docker): {}",
-            audio_path.display()
+            "Transcribing with faster-whisper (Docker): {:.2}s of audio",
+            audio.duration_seconds()
         );
 
         // Ensure container is running
         self.ensure_container_running().await?;
 
-        // Read audio file
-        let file = tokio::fs::read(audio_path).await?;
-
         // Build multipart form - OpenAI compatible API
-        let form = reqwest::multipart::Form::new()
-            .text("model", self.model.clone())
-            .part(
-                "file",
-                reqwest::multipart::Part::bytes(file)
-                    .file_name(
-                        audio_path
-                            .file_name()
-                            .unwrap()
-                            .to_string_lossy()
-                            .to_string(),
-                    )
-                    .mime_str("audio/wav")
-                    .map_err(|e| {
-                        AppError::Transcription(format!("Failed to create multipart: {}", e))
-                    })?,
-            );
+        let form = self.options.form(&self.model, audio, self.upload_format)?;
 
         // Call the faster-whisper API (OpenAI compatible)
-        let url = format!("{}/v1/audio/transcriptions", self.base_url);
+        let url = format!("{}/v1/{}", self.base_url, self.options.endpoint());
         let response = self
             .client
             .post(&url)
@@ -191,14 +280,10 @@ impl TranscriptionBackend for FasterWhisperBackend {
             )));
         }
 
-        let result: serde_json::Value = response.json().await?;
-        let text = result["text"]
-            .as_str()
-            .ok_or_else(|| AppError::Transcription("No text in response".to_string()))?
-            .to_string();
+        let transcript = Transcript::from_response(response.json().await?)?;
 
-        log::info!("Transcription complete: {} characters", text.len());
-        Ok(text)
+        log::info!("Transcription complete: {} characters", transcript.text.len());
+        Ok(transcript)
     }
 
     async fn is_available(&self) -> bool {
@@ -211,31 +296,197 @@ impl TranscriptionBackend for FasterWhisperBackend {
     }
 }
 
+/// How long a server may take to answer an availability probe
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Any server implementing the OpenAI `/v1/audio/transcriptions` API
+///
+/// Covers self-hosted servers such as speaches or LocalAI as well as hosted
+/// gateways like Groq.
+pub struct OpenAICompatibleBackend {
+    client: reqwest::Client,
+    base_url: String,
+    /// Header and value carrying the API key, if one is set
+    auth: Option<(HeaderName, String)>,
+    model: String,
+    upload_format: UploadFormat,
+    timeout: Duration,
+    options: RequestOptions,
+}
+
+impl OpenAICompatibleBackend {
+    pub fn new(config: &TranscriptionConfig, options: RequestOptions) -> Result<Self> {
+        let base_url = config.compatible_base_url.trim().trim_end_matches('/');
+        if base_url.is_empty() {
+            return Err(AppError::Config(
+                "Transcription server URL not configured".to_string(),
+            ));
+        }
+
+        let api_key = config
+            .compatible_api_key
+            .as_deref()
+            .map(str::trim)
+            .filter(|k| !k.is_empty());
+        let auth = match api_key {
+            Some(key) => {
+                let header = HeaderName::from_bytes(config.compatible_auth_header.trim().as_bytes())
+                    .map_err(|e| AppError::Config(format!("Invalid auth header: {}", e)))?;
+                let value = if header == AUTHORIZATION {
+                    format!("Bearer {}", key)
+                } else {
+                    key.to_string()
+                };
+                Some((header, value))
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.to_string(),
+            auth,
+            model: config.model.clone(),
+            upload_format: config.compatible_upload_format,
+            timeout: Duration::from_secs(config.compatible_timeout_seconds),
+            options,
+        })
+    }
+
+    /// Add the API key header, if any
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.auth {
+            Some((header, value)) => request.header(header, value),
+            None => request,
+        }
+    }
+}
+
+#[async_trait]
+impl TranscriptionBackend for OpenAICompatibleBackend {
+    async fn transcribe(&self, audio: &AudioData) -> Result<Transcript> {
+        log::info!(
+            "Transcribing with {}: {:.2}s of audio",
+            self.base_url,
+            audio.duration_seconds()
+        );
+
+        let form = self.options.form(&self.model, audio, self.upload_format)?;
+        let url = format!("{}/{}", self.base_url, self.options.endpoint());
+
+        let response = self
+            .authorize(self.client.post(&url))
+            .multipart(form)
+            .timeout(self.timeout)
+            .send()
+            .await
+            .map_err(|e| {
+                AppError::Transcription(format!("Failed to reach {}: {}", self.base_url, e))
+            })?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(AppError::Transcription(format!(
+                "Server returned {}: {}",
+                status, error_text
+            )));
+        }
+
+        let transcript = Transcript::from_response(response.json().await?)?;
+
+        log::info!("Transcription complete: {} characters", transcript.text.len());
+        Ok(transcript)
+    }
+
+    /// Any answer counts, since not every server implements `/models`
+    async fn is_available(&self) -> bool {
+        self.authorize(self.client.get(format!("{}/models", self.base_url)))
+            .timeout(PROBE_TIMEOUT)
+            .send()
+            .await
+            .is_ok()
+    }
+
+    fn name(&self) -> &str {
+        "OpenAI-compatible server"
+    }
+
+    fn supports_concurrent_requests(&self) -> bool {
+        true
+    }
+}
+
 /// Transcription service that manages backends
 pub struct TranscriptionService {
     backend: Box<dyn TranscriptionBackend>,
+    chunk_seconds: u32,
+    chunk_overlap_ms: u32,
+    chunk_concurrency: usize,
 }
 
 impl TranscriptionService {
-    pub async fn new(config: &TranscriptionConfig) -> Result<Self> {
+    /// Create the configured backend; `whisper_cpp_models` is the session's
+    /// record of models loaded on whisper.cpp servers
+    pub async fn new(
+        config: &TranscriptionConfig,
+        whisper_cpp_models: &LoadedModels,
+    ) -> Result<Self> {
+        let options = RequestOptions::from_config(config);
         let backend: Box<dyn TranscriptionBackend> = match config.backend {
             crate::config::TranscriptionBackend::OpenAI => {
                 let api_key = config
                     .openai_api_key
                     .clone()
                     .ok_or_else(|| AppError::Config("OpenAI API key not configured".to_string()))?;
-                Box::new(OpenAIWhisperBackend::new(api_key, config.model.clone()))
+                Box::new(OpenAIWhisperBackend::new(
+                    api_key,
+                    config.model.clone(),
+                    config.openai_upload_format,
+                    options,
+                ))
             }
             crate::config::TranscriptionBackend::FasterWhisper => {
                 log::info!("Initializing faster-whisper backend with model: {}", config.model);
-                Box::new(FasterWhisperBackend::new(config.model.clone()).await?)
+                Box::new(
+                    FasterWhisperBackend::new(
+                        config.model.clone(),
+                        config.faster_whisper_upload_format,
+                        options,
+                    )
+                    .await?,
+                )
+            }
+            crate::config::TranscriptionBackend::OpenAICompatible => {
+                log::info!(
+                    "Initializing OpenAI-compatible backend at {}",
+                    config.compatible_base_url
+                );
+                Box::new(OpenAICompatibleBackend::new(config, options)?)
+            }
+            crate::config::TranscriptionBackend::WhisperCpp => {
+                log::info!("Initializing whisper.cpp backend at {}", config.whisper_cpp_url);
+                Box::new(WhisperCppBackend::new(
+                    config,
+                    options,
+                    whisper_cpp_models.clone(),
+                )?)
             }
         };
 
-        Ok(Self { backend })
+        Ok(Self {
+            backend,
+            chunk_seconds: config.chunk_seconds,
+            chunk_overlap_ms: config.chunk_overlap_ms,
+            chunk_concurrency: config.chunk_concurrency,
+        })
     }
 
-    pub async fn transcribe(&self, audio_path: &Path) -> Result<String> {
+    pub async fn transcribe(&self, audio: &AudioData) -> Result<Transcript> {
+        if audio.is_empty() {
+            return Err(AppError::Transcription("No audio to transcribe".to_string()));
+        }
+
         if !self.backend.is_available().await {
             return Err(AppError::BackendUnavailable(format!(
                 "{} is not available",
@@ -243,6 +494,241 @@ impl TranscriptionService {
             )));
         }
 
-        self.backend.transcribe(audio_path).await
+        let chunks = chunk::split_at_silence(audio, self.chunk_seconds, self.chunk_overlap_ms);
+        if chunks.len() == 1 {
+            return self.backend.transcribe(audio).await;
+        }
+
+        let concurrency = if self.backend.supports_concurrent_requests() {
+            self.chunk_concurrency.max(1)
+        } else {
+            1
+        };
+        log::info!(
+            "Transcribing {} chunks with {}, {} at a time",
+            chunks.len(),
+            self.backend.name(),
+            concurrency
+        );
+
+        // `buffered` keeps the results in chunk order
+        let requests: Vec<_> = chunks
+            .iter()
+            .map(|c| self.backend.transcribe(&c.audio))
+            .collect();
+        let parts: Vec<Transcript> = stream::iter(requests)
+            .buffered(concurrency)
+            .try_collect()
+            .await?;
+
+        let spans: Vec<_> = chunks
+            .iter()
+            .map(|c| (c.start_seconds, c.end_seconds()))
+            .collect();
+        Ok(transcript::stitch_transcripts(parts, &spans))
+    }
+
+    /// Decode an audio file, prepare it like a recording and transcribe it
+    pub async fn transcribe_file(
+        &self,
+        path: &Path,
+        audio_config: &AudioConfig,
+    ) -> Result<Transcript> {
+        let path = path.to_path_buf();
+        let audio_config = audio_config.clone();
+
+        // Decoding and resampling are CPU-bound, keep them off the async runtime
+        let audio = tokio::task::spawn_blocking(move || {
+            let decoded = crate::audio::decode::decode_file(&path)?;
+            crate::audio::prepare_audio(&decoded, &audio_config)
+        })
+        .await
+        .map_err(|e| AppError::Unknown(format!("Decoding task failed: {}", e)))??;
+
+        self.transcribe(&audio).await
+    }
+
+    /// Check whether the configured backend can currently be used
+    pub async fn is_available(&self) -> bool {
+        self.backend.is_available().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Backend that "hears" a ramp: each sample encodes its own second
+    struct RampBackend {
+        total_seconds: f32,
+    }
+
+    #[async_trait]
+    impl TranscriptionBackend for RampBackend {
+        async fn transcribe(&self, audio: &AudioData) -> Result<Transcript> {
+            let second = |s: f32| (s * self.total_seconds) as usize;
+            let first = second(audio.samples[0]);
+            let last = second(*audio.samples.last().unwrap());
+            Ok(Transcript {
+                text: (first..=last)
+                    .map(|s| format!("word{},", s))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                // Only the opening chunk is long enough to tell
+                language: (first == 0).then(|| "en".to_string()),
+                ..Default::default()
+            })
+        }
+
+        async fn is_available(&self) -> bool {
+            true
+        }
+
+        fn name(&self) -> &str {
+            "ramp"
+        }
+
+        fn supports_concurrent_requests(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_auto_language_is_left_to_the_backend() {
+        let mut config = TranscriptionConfig {
+            language: Some("auto".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(RequestOptions::from_config(&config).language, None);
+
+        config.language = Some(" DE ".to_string());
+        config.task = TranscriptionTask::Translate;
+        let options = RequestOptions::from_config(&config);
+        assert_eq!(options.language.as_deref(), Some("de"));
+        assert_eq!(options.endpoint(), "audio/translations");
+    }
+
+    #[tokio::test]
+    async fn test_long_audio_is_transcribed_in_order() {
+        let rate = 1000;
+        let total_seconds = 25.0;
+        let len = (rate as f32 * total_seconds) as usize;
+        let samples: Vec<f32> = (0..len).map(|i| i as f32 / len as f32).collect();
+
+        let service = TranscriptionService {
+            backend: Box::new(RampBackend { total_seconds }),
+            chunk_seconds: 10,
+            chunk_overlap_ms: 1000,
+            chunk_concurrency: 2,
+        };
+        let transcript = service
+            .transcribe(&AudioData::new(samples, rate, 1))
+            .await
+            .unwrap();
+
+        let expected: Vec<String> = (0..25).map(|s| format!("word{},", s)).collect();
+        assert_eq!(transcript.text, expected.join(" "));
+        assert_eq!(transcript.language.as_deref(), Some("en"));
+    }
+
+    fn compatible_config(base_url: &str) -> TranscriptionConfig {
+        TranscriptionConfig {
+            backend: crate::config::TranscriptionBackend::OpenAICompatible,
+            model: "whisper-large-v3".to_string(),
+            compatible_base_url: base_url.to_string(),
+            compatible_upload_format: UploadFormat::Wav,
+            ..Default::default()
+        }
+    }
+
+    fn speech() -> AudioData {
+        AudioData::new(vec![0.1; 1600], 16000, 1)
+    }
+
+    /// Matches a text field of a multipart body
+    fn form_field(name: &str, value: &str) -> mockito::Matcher {
+        mockito::Matcher::Regex(format!("name=\"{}\"\r\n\r\n{}\r\n", name, value))
+    }
+
+    #[tokio::test]
+    async fn test_compatible_backend_posts_to_configured_server() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/audio/transcriptions")
+            .match_header("x-api-key", "secret")
+            .match_body(mockito::Matcher::AllOf(vec![
+                form_field("model", "whisper-large-v3"),
+                form_field("language", "de"),
+                form_field("response_format", "verbose_json"),
+            ]))
+            .with_body(r#"{"text": "Guten Morgen", "language": "german"}"#)
+            .create_async()
+            .await;
+
+        let mut config = compatible_config(&format!("{}/v1/", server.url()));
+        config.language = Some("de".to_string());
+        config.compatible_api_key = Some("secret".to_string());
+        config.compatible_auth_header = "X-Api-Key".to_string();
+        let backend =
+            OpenAICompatibleBackend::new(&config, RequestOptions::from_config(&config)).unwrap();
+
+        let transcript = backend.transcribe(&speech()).await.unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(transcript.text, "Guten Morgen");
+        assert_eq!(transcript.language.as_deref(), Some("german"));
+    }
+
+    #[tokio::test]
+    async fn test_compatible_backend_translates_with_bearer_token() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/audio/translations")
+            .match_header("authorization", "Bearer sk-test")
+            .with_body(r#"{"text": "Good morning"}"#)
+            .create_async()
+            .await;
+
+        let mut config = compatible_config(&format!("{}/v1", server.url()));
+        config.task = TranscriptionTask::Translate;
+        config.compatible_api_key = Some("sk-test".to_string());
+        let backend =
+            OpenAICompatibleBackend::new(&config, RequestOptions::from_config(&config)).unwrap();
+
+        assert_eq!(backend.transcribe(&speech()).await.unwrap().text, "Good morning");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_compatible_backend_reports_server_errors() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/v1/audio/transcriptions")
+            .with_status(413)
+            .with_body("file too large")
+            .create_async()
+            .await;
+
+        let config = compatible_config(&format!("{}/v1", server.url()));
+        let backend =
+            OpenAICompatibleBackend::new(&config, RequestOptions::from_config(&config)).unwrap();
+
+        let error = backend.transcribe(&speech()).await.unwrap_err().to_string();
+        assert!(error.contains("413") && error.contains("file too large"), "{}", error);
+        assert!(backend.is_available().await);
+    }
+
+    #[tokio::test]
+    async fn test_compatible_backend_unreachable_server() {
+        // Nothing listens on the discard port
+        let config = compatible_config("http://127.0.0.1:9/v1");
+        let backend =
+            OpenAICompatibleBackend::new(&config, RequestOptions::from_config(&config)).unwrap();
+
+        assert!(!backend.is_available().await);
+        assert!(backend.transcribe(&speech()).await.is_err());
+
+        let blank = compatible_config(" ");
+        assert!(OpenAICompatibleBackend::new(&blank, RequestOptions::default()).is_err());
     }
 }
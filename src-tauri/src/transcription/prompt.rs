@@ -0,0 +1,136 @@
+use std::collections::HashSet;
+
+use crate::config::TranscriptionConfig;
+
+/// Whisper only reads the last 224 tokens of a prompt
+const MAX_PROMPT_TOKENS: usize = 224;
+
+/// Vocabulary to bias the backend towards, most relevant first
+///
+/// Terms from the active profile come before the general vocabulary, since
+/// they are specific to what is being dictated right now.
+pub fn vocabulary_terms(config: &TranscriptionConfig) -> Vec<&str> {
+    let profile = config.active_vocabulary_profile.trim();
+    let profile_terms = if profile.is_empty() {
+        None
+    } else {
+        let terms = config.vocabulary_profiles.get(profile);
+        if terms.is_none() {
+            log::warn!("Vocabulary profile '{}' not found", profile);
+        }
+        terms
+    };
+
+    profile_terms
+        .into_iter()
+        .flatten()
+        .chain(&config.vocabulary)
+        .map(String::as_str)
+        .collect()
+}
+
+/// Join vocabulary into a prompt that fits Whisper's limit
+pub fn vocabulary_prompt(terms: &[&str]) -> Option<String> {
+    build_prompt(terms, MAX_PROMPT_TOKENS)
+}
+
+/// Join terms until `budget` tokens are used
+///
+/// Duplicates are dropped case-insensitively. Terms that do not fit are left
+/// out here, since the backend would otherwise cut the prompt from the front
+/// and lose the most relevant ones.
+fn build_prompt(terms: &[&str], budget: usize) -> Option<String> {
+    let mut seen = HashSet::new();
+    let mut kept = Vec::new();
+    let mut used = 0;
+    let mut dropped = 0;
+
+    for term in terms.iter().map(|t| t.trim()) {
+        if term.is_empty() || !seen.insert(term.to_lowercase()) {
+            continue;
+        }
+
+        // One more for the separator
+        let cost = estimate_tokens(term) + 1;
+        if used + cost > budget {
+            dropped += 1;
+            continue;
+        }
+        used += cost;
+        kept.push(term);
+    }
+
+    if dropped > 0 {
+        log::warn!(
+            "Vocabulary exceeds the prompt limit, {} terms left out",
+            dropped
+        );
+    }
+
+    if kept.is_empty() {
+        None
+    } else {
+        Some(kept.join(", "))
+    }
+}
+
+/// Rough token count, erring high
+///
+/// Whisper's tokenizer averages about four characters per token in English;
+/// counting three keeps names and jargon, which split into more pieces,
+/// within the limit.
+fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(3).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_terms_come_first() {
+        let mut config = TranscriptionConfig {
+            vocabulary: vec!["WhisperFlow".to_string(), "Kubernetes".to_string()],
+            ..Default::default()
+        };
+        config
+            .vocabulary_profiles
+            .insert("sales".to_string(), vec!["Acme Corp".to_string()]);
+
+        assert_eq!(vocabulary_terms(&config), vec!["WhisperFlow", "Kubernetes"]);
+
+        config.active_vocabulary_profile = "sales".to_string();
+        assert_eq!(
+            vocabulary_terms(&config),
+            vec!["Acme Corp", "WhisperFlow", "Kubernetes"]
+        );
+
+        // A missing profile still leaves the general vocabulary
+        config.active_vocabulary_profile = "legal".to_string();
+        assert_eq!(vocabulary_terms(&config).len(), 2);
+    }
+
+    #[test]
+    fn test_prompt_skips_duplicates_and_blanks() {
+        assert_eq!(
+            vocabulary_prompt(&["Tauri", " ", "tauri", "Ollama "]).as_deref(),
+            Some("Tauri, Ollama")
+        );
+        assert_eq!(vocabulary_prompt(&[]), None);
+    }
+
+    #[test]
+    fn test_prompt_keeps_most_relevant_terms_within_budget() {
+        // "Postgres" and "Zed" cost 4 and 2 tokens with their separators
+        let terms = ["Postgres", "Kubernetes cluster", "Zed"];
+
+        assert_eq!(build_prompt(&terms, 6).as_deref(), Some("Postgres, Zed"));
+        assert_eq!(build_prompt(&terms, 3).as_deref(), Some("Zed"));
+
+        let many: Vec<String> = (0..500).map(|i| format!("term{}", i)).collect();
+        let many: Vec<&str> = many.iter().map(String::as_str).collect();
+        let prompt = vocabulary_prompt(&many).unwrap();
+        assert!(prompt.starts_with("term0, term1,"));
+        assert!(prompt.split(", ").map(estimate_tokens).sum::<usize>() <= MAX_PROMPT_TOKENS);
+    }
+}
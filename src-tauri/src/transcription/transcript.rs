@@ -0,0 +1,273 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, Result};
+
+/// Longest run of words compared where two chunk transcripts meet
+const MAX_SEAM_WORDS: usize = 12;
+
+/// Text recognized in a piece of audio, with timing where the backend gives it
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Transcript {
+    pub text: String,
+    /// Language the backend detected, if it reports one
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub segments: Vec<Segment>,
+    /// Only filled in when word timestamps were requested
+    #[serde(default)]
+    pub words: Vec<Word>,
+}
+
+/// A stretch of speech, usually a sentence or phrase
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Segment {
+    /// Seconds from the start of the recording
+    pub start: f32,
+    pub end: f32,
+    pub text: String,
+    /// Average log probability of the tokens; below -1 suggests a poor transcription
+    #[serde(default)]
+    pub avg_logprob: Option<f32>,
+    /// Probability that the segment holds no speech at all
+    #[serde(default)]
+    pub no_speech_prob: Option<f32>,
+}
+
+/// A single word with its timing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Word {
+    pub start: f32,
+    pub end: f32,
+    pub word: String,
+}
+
+impl Transcript {
+    /// Read an OpenAI-compatible `json` or `verbose_json` response
+    ///
+    /// Word timings are accepted at the top level, as OpenAI sends them, or
+    /// inside each segment, as whisper.cpp does.
+    pub fn from_response(mut result: serde_json::Value) -> Result<Self> {
+        if !result["text"].is_string() {
+            return Err(AppError::Transcription("No text in response".to_string()));
+        }
+
+        if result.get("words").is_none() {
+            let nested: Vec<serde_json::Value> = result["segments"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|segment| segment["words"].as_array())
+                .flatten()
+                .cloned()
+                .collect();
+            result["words"] = serde_json::Value::Array(nested);
+        }
+
+        let mut transcript: Self = serde_json::from_value(result).map_err(|e| {
+            AppError::Transcription(format!("Unexpected transcription response: {}", e))
+        })?;
+        transcript.language = transcript.language.filter(|l| !l.is_empty());
+        Ok(transcript)
+    }
+
+    /// Move all timestamps `seconds` later
+    fn shift(&mut self, seconds: f32) {
+        for segment in &mut self.segments {
+            segment.start += seconds;
+            segment.end += seconds;
+        }
+        for word in &mut self.words {
+            word.start += seconds;
+            word.end += seconds;
+        }
+    }
+}
+
+/// Join chunk transcripts, dropping words repeated across the overlap
+///
+/// `spans` holds each chunk's start and end in the recording, in seconds.
+/// Where two chunks overlap, segments and words come from the earlier chunk
+/// up to the middle of the overlap and from the later one after it. The
+/// language is taken from the first chunk that reports one.
+pub fn stitch_transcripts(parts: Vec<Transcript>, spans: &[(f32, f32)]) -> Transcript {
+    let mut words: Vec<String> = Vec::new();
+    let mut stitched = Transcript::default();
+
+    for (i, mut part) in parts.into_iter().enumerate() {
+        let next: Vec<&str> = part.text.split_whitespace().collect();
+        let repeated = seam_overlap(&words, &next);
+        words.extend(next[repeated..].iter().map(|w| w.to_string()));
+
+        let (start, end) = spans[i];
+        let from = match i.checked_sub(1) {
+            Some(previous) => (start + spans[previous].1) / 2.0,
+            None => f32::NEG_INFINITY,
+        };
+        let to = match spans.get(i + 1) {
+            Some(following) => (following.0 + end) / 2.0,
+            None => f32::INFINITY,
+        };
+        let owned = |start: f32, end: f32| (from..to).contains(&((start + end) / 2.0));
+
+        part.shift(start);
+        stitched
+            .segments
+            .extend(part.segments.into_iter().filter(|s| owned(s.start, s.end)));
+        stitched
+            .words
+            .extend(part.words.into_iter().filter(|w| owned(w.start, w.end)));
+        stitched.language = stitched.language.or(part.language);
+    }
+
+    stitched.text = words.join(" ");
+    stitched
+}
+
+/// Number of leading words of `next` that repeat the end of `previous`
+fn seam_overlap(previous: &[String], next: &[&str]) -> usize {
+    let longest = MAX_SEAM_WORDS.min(previous.len()).min(next.len());
+    (1..=longest)
+        .rev()
+        .find(|&n| {
+            previous[previous.len() - n..]
+                .iter()
+                .zip(&next[..n])
+                .all(|(a, b)| normalize_word(a) == normalize_word(b))
+        })
+        .unwrap_or(0)
+}
+
+/// Lowercase a word and drop punctuation, so "Hello," matches "hello"
+fn normalize_word(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transcripts(parts: &[&str]) -> Vec<Transcript> {
+        parts
+            .iter()
+            .map(|text| Transcript {
+                text: text.to_string(),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn segment(start: f32, end: f32, text: &str) -> Segment {
+        Segment {
+            start,
+            end,
+            text: text.to_string(),
+            avg_logprob: None,
+            no_speech_prob: None,
+        }
+    }
+
+    #[test]
+    fn test_stitch_removes_repeated_seam() {
+        let parts = transcripts(&[
+            "So the plan is to ship",
+            "to ship it on Friday, and then",
+            "And then we rest.",
+        ]);
+        let spans = [(0.0, 10.0), (9.0, 19.0), (18.0, 25.0)];
+
+        assert_eq!(
+            stitch_transcripts(parts, &spans).text,
+            "So the plan is to ship it on Friday, and then we rest."
+        );
+    }
+
+    #[test]
+    fn test_stitch_keeps_unrelated_words() {
+        let parts = transcripts(&["first part", "second part"]);
+        let spans = [(0.0, 10.0), (9.0, 15.0)];
+
+        assert_eq!(
+            stitch_transcripts(parts, &spans).text,
+            "first part second part"
+        );
+    }
+
+    #[test]
+    fn test_stitch_moves_segments_into_recording_time() {
+        // Chunks overlap from 9 to 10 s, so each owns its side of 9.5 s
+        let first = Transcript {
+            text: "One. Two.".to_string(),
+            segments: vec![segment(0.0, 4.0, "One."), segment(8.8, 9.8, "Two.")],
+            ..Default::default()
+        };
+        let second = Transcript {
+            text: "Two. Three.".to_string(),
+            segments: vec![segment(0.0, 0.8, "Two."), segment(1.0, 3.0, "Three.")],
+            words: vec![Word {
+                start: 1.0,
+                end: 3.0,
+                word: "Three.".to_string(),
+            }],
+            language: Some("en".to_string()),
+        };
+
+        let stitched = stitch_transcripts(vec![first, second], &[(0.0, 10.0), (9.0, 12.0)]);
+
+        assert_eq!(stitched.text, "One. Two. Three.");
+        assert_eq!(
+            stitched.segments,
+            vec![
+                segment(0.0, 4.0, "One."),
+                segment(8.8, 9.8, "Two."),
+                segment(10.0, 12.0, "Three."),
+            ]
+        );
+        assert_eq!(stitched.words[0].start, 10.0);
+        assert_eq!(stitched.language.as_deref(), Some("en"));
+    }
+
+    #[test]
+    fn test_parse_verbose_response() {
+        let verbose = serde_json::json!({
+            "task": "transcribe",
+            "language": "german",
+            "duration": 2.5,
+            "text": "Hallo zusammen",
+            "segments": [{
+                "id": 0,
+                "seek": 0,
+                "start": 0.0,
+                "end": 2.5,
+                "text": "Hallo zusammen",
+                "tokens": [50364, 2425],
+                "temperature": 0.0,
+                "avg_logprob": -0.25,
+                "compression_ratio": 0.8,
+                "no_speech_prob": 0.01
+            }],
+            "words": [{ "word": "Hallo", "start": 0.0, "end": 0.9 }]
+        });
+
+        let transcript = Transcript::from_response(verbose).unwrap();
+
+        assert_eq!(transcript.language.as_deref(), Some("german"));
+        assert_eq!(transcript.segments.len(), 1);
+        assert_eq!(transcript.segments[0].avg_logprob, Some(-0.25));
+        assert_eq!(transcript.segments[0].no_speech_prob, Some(0.01));
+        assert_eq!(transcript.words[0].word, "Hallo");
+    }
+
+    #[test]
+    fn test_parse_plain_response() {
+        let plain = Transcript::from_response(serde_json::json!({ "text": "Hello" })).unwrap();
+        assert_eq!(plain.text, "Hello");
+        assert_eq!(plain.language, None);
+        assert!(plain.segments.is_empty());
+
+        assert!(Transcript::from_response(serde_json::json!({})).is_err());
+    }
+}
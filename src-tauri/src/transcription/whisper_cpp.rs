@@ -0,0 +1,464 @@
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::{audio_part, RequestOptions, Transcript, TranscriptionBackend, PROBE_TIMEOUT};
+use crate::audio::{resample, AudioData};
+use crate::config::{TranscriptionConfig, TranscriptionTask, UploadFormat};
+use crate::error::{AppError, Result};
+
+/// whisper.cpp only reads 16 kHz audio unless the server was built with conversion
+const WHISPER_SAMPLE_RATE: u32 = 16000;
+
+/// How long to wait for a server that is still loading its model
+const READY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Time allowed for one transcription or model load
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Model last loaded on each server, by URL
+///
+/// The server cannot be asked which model it has, and a backend only lives
+/// for one transcription, so the app keeps this for the session. An entry is
+/// dropped whenever the server may have restarted with its default model.
+#[derive(Debug, Clone, Default)]
+pub struct LoadedModels(Arc<Mutex<BTreeMap<String, String>>>);
+
+impl LoadedModels {
+    fn get(&self, url: &str) -> Option<String> {
+        self.0.lock().unwrap().get(url).cloned()
+    }
+
+    fn set(&self, url: &str, model_path: &str) {
+        self.0
+            .lock()
+            .unwrap()
+            .insert(url.to_string(), model_path.to_string());
+    }
+
+    fn forget(&self, url: &str) {
+        self.0.lock().unwrap().remove(url);
+    }
+}
+
+/// State reported by the server's `/health` endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Health {
+    Ready,
+    Loading,
+    Unreachable,
+}
+
+/// whisper.cpp's `server` binary, running natively rather than in Docker
+pub struct WhisperCppBackend {
+    client: reqwest::Client,
+    base_url: String,
+    /// Model file on the server's machine; empty keeps whatever is loaded
+    model_path: String,
+    temperature: f32,
+    options: RequestOptions,
+    loaded_models: LoadedModels,
+}
+
+impl WhisperCppBackend {
+    pub fn new(
+        config: &TranscriptionConfig,
+        options: RequestOptions,
+        loaded_models: LoadedModels,
+    ) -> Result<Self> {
+        let base_url = config.whisper_cpp_url.trim().trim_end_matches('/');
+        if base_url.is_empty() {
+            return Err(AppError::Config(
+                "whisper.cpp server URL not configured".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.to_string(),
+            model_path: config.whisper_cpp_model_path.trim().to_string(),
+            temperature: config.whisper_cpp_temperature,
+            options,
+            loaded_models,
+        })
+    }
+
+    async fn health(&self) -> Health {
+        let response = self
+            .client
+            .get(format!("{}/health", self.base_url))
+            .timeout(PROBE_TIMEOUT)
+            .send()
+            .await;
+
+        match response {
+            Ok(response) if response.status().is_success() => Health::Ready,
+            // 503 while a model is loading
+            Ok(response) if response.status() == reqwest::StatusCode::SERVICE_UNAVAILABLE => {
+                Health::Loading
+            }
+            // Older builds have no health endpoint; make sure it is whisper.cpp at all
+            Ok(response) if response.status() == reqwest::StatusCode::NOT_FOUND => {
+                if self.serves_whisper_cpp_page().await {
+                    log::info!("whisper.cpp server has no /health endpoint, assuming ready");
+                    Health::Ready
+                } else {
+                    log::warn!(
+                        "{} has no /health endpoint and is not a whisper.cpp server",
+                        self.base_url
+                    );
+                    Health::Unreachable
+                }
+            }
+            Ok(response) => {
+                log::warn!("whisper.cpp health check returned {}", response.status());
+                Health::Unreachable
+            }
+            Err(_) => Health::Unreachable,
+        }
+    }
+
+    /// Whether the server's start page is the one whisper.cpp serves
+    async fn serves_whisper_cpp_page(&self) -> bool {
+        let response = self
+            .client
+            .get(format!("{}/", self.base_url))
+            .timeout(PROBE_TIMEOUT)
+            .send()
+            .await;
+
+        match response {
+            Ok(response) if response.status().is_success() => response
+                .text()
+                .await
+                .is_ok_and(|page| page.to_lowercase().contains("whisper.cpp")),
+            _ => false,
+        }
+    }
+
+    /// Wait until the server can take requests
+    ///
+    /// A server that is down or loading may have restarted, so the model it
+    /// had is forgotten.
+    async fn wait_until_ready(&self) -> Result<()> {
+        let started = Instant::now();
+        loop {
+            let health = self.health().await;
+            if health != Health::Ready {
+                self.loaded_models.forget(&self.base_url);
+            }
+
+            match health {
+                Health::Ready => return Ok(()),
+                Health::Unreachable => {
+                    return Err(AppError::BackendUnavailable(format!(
+                        "whisper.cpp server not reachable at {}",
+                        self.base_url
+                    )))
+                }
+                Health::Loading if started.elapsed() >= READY_TIMEOUT => {
+                    return Err(AppError::BackendUnavailable(
+                        "whisper.cpp server is still loading its model".to_string(),
+                    ))
+                }
+                Health::Loading => tokio::time::sleep(Duration::from_millis(500)).await,
+            }
+        }
+    }
+
+    /// Make the server use `model_path`, loading it only if it changed
+    pub async fn load_model(&self, model_path: &str) -> Result<()> {
+        if self.loaded_models.get(&self.base_url).as_deref() == Some(model_path) {
+            return Ok(());
+        }
+
+        log::info!("Loading whisper.cpp model: {}", model_path);
+        let form = reqwest::multipart::Form::new().text("model", model_path.to_string());
+        let response = self
+            .client
+            .post(format!("{}/load", self.base_url))
+            .multipart(form)
+            .timeout(REQUEST_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| {
+                AppError::Transcription(format!("Failed to reach whisper.cpp server: {}", e))
+            })?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(AppError::Transcription(format!(
+                "Failed to load model {}: {}",
+                model_path, error_text
+            )));
+        }
+
+        self.loaded_models.set(&self.base_url, model_path);
+        Ok(())
+    }
+
+    /// Wait for the server and switch to the configured model
+    pub async fn prepare(&self) -> Result<()> {
+        self.wait_until_ready().await?;
+        if !self.model_path.is_empty() {
+            self.load_model(&self.model_path).await?;
+        }
+        Ok(())
+    }
+
+    fn form(&self, audio: &AudioData) -> Result<reqwest::multipart::Form> {
+        let mono = AudioData::new(
+            resample::convert(
+                &audio.samples,
+                audio.channels,
+                audio.sample_rate,
+                1,
+                WHISPER_SAMPLE_RATE,
+            ),
+            WHISPER_SAMPLE_RATE,
+            1,
+        );
+        let translate = self.options.task == TranscriptionTask::Translate;
+
+        let mut form = reqwest::multipart::Form::new()
+            .part("file", audio_part(&mono, UploadFormat::Wav)?)
+            .text("response_format", "verbose_json")
+            .text("temperature", self.temperature.to_string())
+            .text("translate", translate.to_string())
+            .text(
+                "language",
+                self.options
+                    .language
+                    .clone()
+                    .unwrap_or_else(|| "auto".to_string()),
+            );
+        if let Some(prompt) = &self.options.prompt {
+            form = form.text("prompt", prompt.clone());
+        }
+
+        Ok(form)
+    }
+}
+
+#[async_trait]
+impl TranscriptionBackend for WhisperCppBackend {
+    async fn transcribe(&self, audio: &AudioData) -> Result<Transcript> {
+        log::info!(
+            "Transcribing with whisper.cpp: {:.2}s of audio",
+            audio.duration_seconds()
+        );
+
+        self.prepare().await?;
+
+        // A failed request may mean the server restarted with another model
+        let response = self
+            .client
+            .post(format!("{}/inference", self.base_url))
+            .multipart(self.form(audio)?)
+            .timeout(REQUEST_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| {
+                self.loaded_models.forget(&self.base_url);
+                AppError::Transcription(format!("Failed to reach whisper.cpp server: {}", e))
+            })?;
+
+        if !response.status().is_success() {
+            self.loaded_models.forget(&self.base_url);
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(AppError::Transcription(format!(
+                "Transcription failed: {}",
+                error_text
+            )));
+        }
+
+        let transcript = Transcript::from_response(response.json().await?)?;
+
+        log::info!(
+            "Transcription complete: {} characters",
+            transcript.text.len()
+        );
+        Ok(transcript)
+    }
+
+    async fn is_available(&self) -> bool {
+        self.health().await != Health::Unreachable
+    }
+
+    fn name(&self) -> &str {
+        "whisper.cpp server"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backend(url: &str, model_path: &str) -> WhisperCppBackend {
+        let config = TranscriptionConfig {
+            whisper_cpp_url: url.to_string(),
+            whisper_cpp_model_path: model_path.to_string(),
+            language: Some("en".to_string()),
+            ..Default::default()
+        };
+        WhisperCppBackend::new(
+            &config,
+            RequestOptions::from_config(&config),
+            LoadedModels::default(),
+        )
+        .unwrap()
+    }
+
+    fn form_field(name: &str, value: &str) -> mockito::Matcher {
+        mockito::Matcher::Regex(format!("name=\"{}\"\r\n\r\n{}\r\n", name, value))
+    }
+
+    #[tokio::test]
+    async fn test_transcribes_through_inference_endpoint() {
+        let mut server = mockito::Server::new_async().await;
+        server.mock("GET", "/health").create_async().await;
+        let inference = server
+            .mock("POST", "/inference")
+            .match_body(mockito::Matcher::AllOf(vec![
+                form_field("response_format", "verbose_json"),
+                form_field("temperature", "0"),
+                form_field("language", "en"),
+                form_field("translate", "false"),
+            ]))
+            .with_body(
+                r#"{
+                    "language": "english",
+                    "text": " Hello there.",
+                    "segments": [{
+                        "id": 0, "text": " Hello there.", "start": 0.0, "end": 1.2,
+                        "avg_logprob": -0.2, "no_speech_prob": 0.02,
+                        "words": [{"word": " Hello", "start": 0.0, "end": 0.5, "probability": 0.9}]
+                    }]
+                }"#,
+            )
+            .create_async()
+            .await;
+
+        // Stereo 48 kHz is converted before upload
+        let audio = AudioData::new(vec![0.1; 9600], 48000, 2);
+        let transcript = backend(&server.url(), "").transcribe(&audio).await.unwrap();
+
+        inference.assert_async().await;
+        assert_eq!(transcript.language.as_deref(), Some("english"));
+        assert_eq!(transcript.segments[0].end, 1.2);
+        assert_eq!(transcript.words[0].word, " Hello");
+    }
+
+    #[tokio::test]
+    async fn test_model_is_loaded_once() {
+        let mut server = mockito::Server::new_async().await;
+        server.mock("GET", "/health").create_async().await;
+        let load = server
+            .mock("POST", "/load")
+            .match_body(form_field("model", "models/ggml-small.bin"))
+            .with_body("Load was successful!")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let backend = backend(&server.url(), "models/ggml-small.bin");
+        backend.prepare().await.unwrap();
+        backend.prepare().await.unwrap();
+
+        load.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_model_is_reloaded_after_failed_inference() {
+        let mut server = mockito::Server::new_async().await;
+        server.mock("GET", "/health").create_async().await;
+        let load = server
+            .mock("POST", "/load")
+            .with_body("Load was successful!")
+            .expect(2)
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/inference")
+            .with_status(500)
+            .create_async()
+            .await;
+
+        // The server may have restarted with its default model
+        let backend = backend(&server.url(), "models/ggml-small.bin");
+        let audio = AudioData::new(vec![0.1; 1600], 16000, 1);
+        assert!(backend.transcribe(&audio).await.is_err());
+        backend.prepare().await.unwrap();
+
+        load.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_health_probing() {
+        let mut server = mockito::Server::new_async().await;
+        let loading = server
+            .mock("GET", "/health")
+            .with_status(503)
+            .with_body(r#"{"status": "loading model"}"#)
+            .create_async()
+            .await;
+
+        let local = backend(&server.url(), "");
+        assert_eq!(local.health().await, Health::Loading);
+        assert!(local.is_available().await);
+
+        loading.remove_async().await;
+        server.mock("GET", "/health").create_async().await;
+        assert_eq!(local.health().await, Health::Ready);
+
+        // Nothing listens on the discard port
+        let offline = backend("http://127.0.0.1:9", "");
+        assert!(!offline.is_available().await);
+        assert!(matches!(
+            offline.prepare().await,
+            Err(AppError::BackendUnavailable(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_server_without_health_endpoint_is_ready() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/health")
+            .with_status(404)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/")
+            .with_body("<html><head><title>Whisper.cpp Server</title></head></html>")
+            .create_async()
+            .await;
+
+        let local = backend(&server.url(), "");
+        assert_eq!(local.health().await, Health::Ready);
+        assert!(local.prepare().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_other_server_without_health_endpoint_is_unreachable() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/health")
+            .with_status(404)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/")
+            .with_body("<html><head><title>Vite App</title></head></html>")
+            .create_async()
+            .await;
+
+        let local = backend(&server.url(), "");
+        assert_eq!(local.health().await, Health::Unreachable);
+        assert!(matches!(
+            local.prepare().await,
+            Err(AppError::BackendUnavailable(_))
+        ));
+    }
+}
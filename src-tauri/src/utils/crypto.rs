@@ -0,0 +1,97 @@
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+
+use crate::error::{AppError, Result};
+
+/// Encryption key generated at startup and held only in memory
+///
+/// Anything sealed with it becomes unreadable once the app exits, which is
+/// the point: temporary audio left behind by a crash cannot be played back.
+pub struct SessionKey {
+    key: LessSafeKey,
+    rng: SystemRandom,
+}
+
+impl SessionKey {
+    pub fn generate() -> Result<Self> {
+        let rng = SystemRandom::new();
+        let mut bytes = [0u8; 32];
+        rng.fill(&mut bytes)
+            .map_err(|_| AppError::Unknown("Failed to generate session key".to_string()))?;
+
+        let key = UnboundKey::new(&CHACHA20_POLY1305, &bytes)
+            .map_err(|_| AppError::Unknown("Invalid session key".to_string()))?;
+
+        Ok(Self {
+            key: LessSafeKey::new(key),
+            rng,
+        })
+    }
+
+    /// Encrypt and authenticate `plaintext`, prefixed with its random nonce
+    pub fn seal(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce)
+            .map_err(|_| AppError::Unknown("Failed to generate nonce".to_string()))?;
+
+        let mut sealed = nonce.to_vec();
+        let mut body = plaintext.to_vec();
+        self.key
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut body)
+            .map_err(|_| AppError::Unknown("Encryption failed".to_string()))?;
+        sealed.append(&mut body);
+
+        Ok(sealed)
+    }
+
+    /// Decrypt data produced by [`seal`](Self::seal) with this key
+    pub fn open(&self, sealed: &[u8]) -> Result<Vec<u8>> {
+        if sealed.len() < NONCE_LEN {
+            return Err(AppError::InvalidState("Encrypted data is truncated".to_string()));
+        }
+
+        let (nonce, body) = sealed.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce)
+            .map_err(|_| AppError::InvalidState("Invalid nonce".to_string()))?;
+        let mut body = body.to_vec();
+        let plaintext = self
+            .key
+            .open_in_place(nonce, Aad::empty(), &mut body)
+            .map_err(|_| {
+                AppError::InvalidState("Data was not encrypted with this session's key".to_string())
+            })?;
+
+        Ok(plaintext.to_vec())
+    }
+}
+
+impl std::fmt::Debug for SessionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SessionKey(..)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_round_trip() {
+        let key = SessionKey::generate().unwrap();
+        let plaintext = b"RIFF speech that should not be readable";
+
+        let sealed = key.seal(plaintext).unwrap();
+
+        assert!(!sealed.windows(4).any(|w| w == b"RIFF"));
+        assert_eq!(key.open(&sealed).unwrap(), plaintext);
+    }
+
+    #[test]
+    fn test_other_session_cannot_open() {
+        let sealed = SessionKey::generate().unwrap().seal(b"audio").unwrap();
+        let other = SessionKey::generate().unwrap();
+
+        assert!(other.open(&sealed).is_err());
+    }
+}
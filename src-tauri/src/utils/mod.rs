@@ -1,13 +1,36 @@
+pub mod crypto;
+
 use std::path::Path;
 use tokio::fs;
+use tokio::io::AsyncWriteExt;
 
 use crate::error::Result;
 
 /// Securely delete a file by overwriting with zeros first (paranoid mode)
+///
+/// Best effort: copy-on-write filesystems and SSD wear levelling can keep the
+/// old blocks, which is why temporary audio can also be encrypted.
 pub async fn secure_delete_file(path: &Path) -> Result<()> {
-    // For MVP, just delete normally (secure overwrite planned for paranoid mode)
+    overwrite_with_zeros(path).await?;
     fs::remove_file(path).await?;
-    log::debug!("Deleted file: {}", path.display());
+    log::debug!("Securely deleted file: {}", path.display());
+    Ok(())
+}
+
+/// Replace a file's contents with zeros in place and flush them to disk
+async fn overwrite_with_zeros(path: &Path) -> Result<()> {
+    let len = fs::metadata(path).await?.len();
+    let mut file = fs::OpenOptions::new().write(true).open(path).await?;
+
+    let zeros = vec![0u8; 64 * 1024];
+    let mut remaining = len;
+    while remaining > 0 {
+        let chunk = remaining.min(zeros.len() as u64) as usize;
+        file.write_all(&zeros[..chunk]).await?;
+        remaining -= chunk as u64;
+    }
+    file.sync_all().await?;
+
     Ok(())
 }
 
@@ -41,3 +64,23 @@ pub fn format_duration(seconds: f64) -> String {
         format!("{}m {:.0}s", minutes, secs)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_secure_delete_overwrites_then_removes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("recording.wav");
+        std::fs::write(&path, vec![0xAB; 100_000]).unwrap();
+
+        overwrite_with_zeros(&path).await.unwrap();
+        let contents = std::fs::read(&path).unwrap();
+        assert_eq!(contents.len(), 100_000);
+        assert!(contents.iter().all(|&b| b == 0));
+
+        secure_delete_file(&path).await.unwrap();
+        assert!(!path.exists());
+    }
+}
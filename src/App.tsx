@@ -1,10 +1,20 @@
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { open } from "@tauri-apps/plugin-dialog";
 import RecordingButton from "./components/RecordingButton";
 import StatusIndicator from "./components/StatusIndicator";
 import SettingsPanel from "./components/SettingsPanel";
 import FirstRunWizard from "./components/FirstRunWizard";
-import { RecordingState, AppConfig } from "./types";
+import {
+  RecordingState,
+  AppConfig,
+  AudioLevel,
+  AutoStopEvent,
+  ProcessedResult,
+  RecoverableRecording,
+  TimeLimitEvent,
+} from "./types";
 import "./App.css";
 
 function App() {
@@ -13,15 +23,53 @@ function App() {
   const [showSettings, setShowSettings] = useState(false);
   const [showWizard, setShowWizard] = useState(false);
   const [error, setError] = useState<string | null>(null);
-  const [lastResult, setLastResult] = useState<{
-    transcription: string;
-    cleaned_text: string;
-  } | null>(null);
+  const [notice, setNotice] = useState<string | null>(null);
+  const [inputLevel, setInputLevel] = useState<number | undefined>(undefined);
+  const [lastResult, setLastResult] = useState<ProcessedResult | null>(null);
+  const [isTranscribingFile, setIsTranscribingFile] = useState(false);
+  const [recoverable, setRecoverable] = useState<RecoverableRecording[]>([]);
 
   // Load config and check first run on mount
   useEffect(() => {
     loadConfig();
     checkFirstRun();
+    loadRecoverable();
+  }, []);
+
+  // Follow recordings the backend stops on its own (e.g. trailing silence)
+  useEffect(() => {
+    const unlisteners = Promise.all([
+      listen<AutoStopEvent>("recording-auto-stopped", (event) => {
+        console.log("Recording auto-stopped:", event.payload.reason);
+        setNotice(
+          event.payload.reason === "TimeLimit" ? "Recording time limit reached" : null
+        );
+        setRecordingState({ type: "Processing", data: { stage: "Transcribing" } });
+      }),
+      listen<ProcessedResult>("recording-processed", (event) => {
+        showResult(event.payload);
+        setRecordingState({ type: "Idle" });
+      }),
+      listen<AudioLevel>("audio-level", (event) => {
+        // Scale RMS up so normal speech fills most of the meter
+        setInputLevel(Math.min(event.payload.rms * 4, 1));
+      }),
+      listen<TimeLimitEvent>("recording-time-limit-approaching", (event) => {
+        setNotice(`Recording stops in ${event.payload.remaining_seconds} seconds`);
+      }),
+      listen<string>("recording-device-changed", (event) => {
+        setNotice(`Microphone disconnected, recording continues on ${event.payload}`);
+      }),
+      listen<string>("recording-failed", (event) => {
+        setError(event.payload);
+        setRecordingState({ type: "Idle" });
+        loadRecoverable();
+      }),
+    ]);
+
+    return () => {
+      unlisteners.then((fns) => fns.forEach((unlisten) => unlisten()));
+    };
   }, []);
 
   const checkFirstRun = () => {
@@ -41,9 +89,28 @@ function App() {
     }
   };
 
+  // Recordings that were never processed, e.g. because the app crashed
+  const loadRecoverable = async () => {
+    try {
+      setRecoverable(await invoke<RecoverableRecording[]>("list_recoverable_recordings"));
+    } catch (err) {
+      console.error("Failed to list recoverable recordings:", err);
+    }
+  };
+
+  // A recording without speech comes back empty rather than failing
+  const showResult = (result: ProcessedResult) => {
+    if (result.transcription) {
+      setLastResult(result);
+    } else {
+      setNotice("No speech detected");
+    }
+  };
+
   const handleStartRecording = async () => {
     try {
       setError(null);
+      setNotice(null);
       await invoke("start_recording");
       setRecordingState({ type: "Recording", data: { started_at: Date.now() } });
     } catch (err) {
@@ -57,10 +124,13 @@ function App() {
       setError(null);
       setRecordingState({ type: "Processing", data: { stage: "Transcribing" } });
 
-      const result = await invoke<{ transcription: string; cleaned_text: string }>(
-        "stop_recording"
-      );
-      setLastResult(result);
+      const result = await invoke<ProcessedResult | null>("stop_recording");
+      // Nothing to stop: an auto-stop is already processing the recording
+      // and reports through "recording-processed"
+      if (!result) {
+        return;
+      }
+      showResult(result);
       setRecordingState({ type: "Idle" });
 
       // Show success notification
@@ -69,6 +139,28 @@ function App() {
       console.error("Failed to stop recording:", err);
       setError(err as string);
       setRecordingState({ type: "Idle" });
+      loadRecoverable();
+    }
+  };
+
+  const handlePauseRecording = async () => {
+    try {
+      await invoke("pause_recording");
+      setRecordingState(await invoke<RecordingState>("get_recording_state"));
+      setInputLevel(undefined);
+    } catch (err) {
+      console.error("Failed to pause recording:", err);
+      setError(err as string);
+    }
+  };
+
+  const handleResumeRecording = async () => {
+    try {
+      await invoke("resume_recording");
+      setRecordingState(await invoke<RecordingState>("get_recording_state"));
+    } catch (err) {
+      console.error("Failed to resume recording:", err);
+      setError(err as string);
     }
   };
 
@@ -82,6 +174,55 @@ function App() {
     }
   };
 
+  const handleTranscribeFile = async () => {
+    const path = await open({
+      multiple: false,
+      filters: [{ name: "Audio", extensions: ["wav", "flac", "mp3", "ogg", "oga", "opus"] }],
+    });
+    if (!path) return;
+
+    try {
+      setError(null);
+      setIsTranscribingFile(true);
+      const result = await invoke<ProcessedResult>("transcribe_file", { path, rewrite: true });
+      setLastResult(result);
+    } catch (err) {
+      console.error("Failed to transcribe file:", err);
+      setError(err as string);
+    } finally {
+      setIsTranscribingFile(false);
+    }
+  };
+
+  const handleRecoverRecording = async (recording: RecoverableRecording) => {
+    try {
+      setError(null);
+      setIsTranscribingFile(true);
+      const result = await invoke<ProcessedResult>("recover_recording", {
+        path: recording.path,
+        rewrite: true,
+      });
+      setLastResult(result);
+    } catch (err) {
+      console.error("Failed to recover recording:", err);
+      setError(err as string);
+    } finally {
+      setIsTranscribingFile(false);
+      loadRecoverable();
+    }
+  };
+
+  const handleDiscardRecording = async (recording: RecoverableRecording) => {
+    try {
+      await invoke("discard_recording", { path: recording.path });
+    } catch (err) {
+      console.error("Failed to discard recording:", err);
+      setError(err as string);
+    } finally {
+      loadRecoverable();
+    }
+  };
+
   const handleUpdateConfig = async (newConfig: AppConfig) => {
     try {
       await invoke("update_config", { config: newConfig });
@@ -99,6 +240,7 @@ function App() {
   };
 
   const isRecording = recordingState.type === "Recording";
+  const isPaused = recordingState.type === "Paused";
   const isProcessing = recordingState.type === "Processing";
 
   // Show wizard if first run
@@ -121,18 +263,66 @@ function App() {
             <StatusIndicator state={recordingState} />
 
             <RecordingButton
-              isRecording={isRecording}
+              isRecording={isRecording || isPaused}
               isProcessing={isProcessing}
               onStart={handleStartRecording}
               onStop={handleStopRecording}
+              level={inputLevel}
             />
 
-            {isRecording && (
-              <button className="cancel-btn" onClick={handleCancelRecording}>
-                Cancel
+            {(isRecording || isPaused) && (
+              <div className="recording-controls">
+                <button
+                  className="pause-btn"
+                  onClick={isPaused ? handleResumeRecording : handlePauseRecording}
+                >
+                  {isPaused ? "Resume" : "Pause"}
+                </button>
+                <button className="cancel-btn" onClick={handleCancelRecording}>
+                  Cancel
+                </button>
+              </div>
+            )}
+
+            {recordingState.type === "Idle" && (
+              <button
+                className="file-btn"
+                onClick={handleTranscribeFile}
+                disabled={isTranscribingFile}
+              >
+                {isTranscribingFile ? "Transcribing file..." : "Transcribe audio file"}
               </button>
             )}
 
+            {recordingState.type === "Idle" && recoverable.length > 0 && (
+              <div className="notice-message">
+                <p>
+                  {recoverable.length === 1
+                    ? "A recording was never processed."
+                    : `${recoverable.length} recordings were never processed.`}{" "}
+                  Recorded {new Date(recoverable[0].recorded_at).toLocaleString()}.
+                </p>
+                <div className="recording-controls">
+                  <button
+                    className="file-btn"
+                    onClick={() => handleRecoverRecording(recoverable[0])}
+                    disabled={isTranscribingFile}
+                  >
+                    Transcribe
+                  </button>
+                  <button
+                    className="file-btn"
+                    onClick={() => handleDiscardRecording(recoverable[0])}
+                    disabled={isTranscribingFile}
+                  >
+                    Discard
+                  </button>
+                </div>
+              </div>
+            )}
+
+            {notice && <div className="notice-message">{notice}</div>}
+
             {error && (
               <div className="error-message">
                 <strong>Error:</strong> {error}
@@ -142,6 +332,9 @@ function App() {
             {lastResult && (
               <div className="result-display">
                 <h3>Last Result:</h3>
+                {lastResult.language && (
+                  <p className="tip">Detected language: {lastResult.language}</p>
+                )}
                 <div className="result-section">
                   <h4>Transcription:</h4>
                   <p>{lastResult.transcription}</p>
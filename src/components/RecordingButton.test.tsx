@@ -78,6 +78,21 @@ describe("RecordingButton", () => {
     expect(button).toHaveClass("recording");
   });
 
+  it("shows the input level meter while recording", () => {
+    render(
+      <RecordingButton
+        isRecording={true}
+        isProcessing={false}
+        onStart={vi.fn()}
+        onStop={vi.fn()}
+        level={0.5}
+      />
+    );
+
+    const meter = screen.getByRole("meter");
+    expect(meter).toHaveAttribute("aria-valuenow", "0.5");
+  });
+
   it("applies processing class when processing", () => {
     const onStart = vi.fn();
     const onStop = vi.fn();
@@ -5,6 +5,8 @@ interface RecordingButtonProps {
   isProcessing: boolean;
   onStart: () => void;
   onStop: () => void;
+  /** Live input level (0-1) while recording */
+  level?: number;
 }
 
 export default function RecordingButton({
@@ -12,6 +14,7 @@ export default function RecordingButton({
   isProcessing,
   onStart,
   onStop,
+  level,
 }: RecordingButtonProps) {
   const handleClick = () => {
     if (isRecording) {
@@ -34,6 +37,11 @@ export default function RecordingButton({
       <div className="button-text">
         {isProcessing ? "Processing..." : isRecording ? "Stop" : "Start Recording"}
       </div>
+      {isRecording && level !== undefined && (
+        <div className="level-meter" role="meter" aria-label="Input level" aria-valuenow={level}>
+          <div className="level-meter-fill" style={{ width: `${Math.min(level, 1) * 100}%` }} />
+        </div>
+      )}
     </button>
   );
 }
@@ -104,7 +104,7 @@ export default function SettingsPanel({ config, onUpdate, onClose }: SettingsPan
     onClose();
   };
 
-  const updateTranscription = (field: string, value: string | null) => {
+  const updateTranscription = (field: string, value: string | string[] | number | null) => {
     setLocalConfig({
       ...localConfig,
       transcription: { ...localConfig.transcription, [field]: value },
@@ -133,9 +133,99 @@ export default function SettingsPanel({ config, onUpdate, onClose }: SettingsPan
           >
             <option value="OpenAI">OpenAI Whisper API (Cloud)</option>
             <option value="FasterWhisper">faster-whisper (Local - Docker)</option>
+            <option value="OpenAICompatible">OpenAI-compatible server</option>
+            <option value="WhisperCpp">whisper.cpp server (Local - Native)</option>
           </select>
         </label>
 
+        {localConfig.transcription.backend === "WhisperCpp" && (
+          <>
+            <label>
+              Server URL:
+              <input
+                type="text"
+                value={localConfig.transcription.whisper_cpp_url}
+                onChange={(e) => updateTranscription("whisper_cpp_url", e.target.value)}
+                placeholder="http://127.0.0.1:8080"
+              />
+            </label>
+            <label>
+              Model file:
+              <input
+                type="text"
+                value={localConfig.transcription.whisper_cpp_model_path}
+                onChange={(e) => updateTranscription("whisper_cpp_model_path", e.target.value)}
+                placeholder="Keep the model the server started with"
+              />
+            </label>
+            <label>
+              Temperature:
+              <input
+                type="number"
+                min={0}
+                max={1}
+                step={0.1}
+                value={localConfig.transcription.whisper_cpp_temperature}
+                onChange={(e) =>
+                  updateTranscription("whisper_cpp_temperature", Number(e.target.value))
+                }
+              />
+            </label>
+          </>
+        )}
+
+        {localConfig.transcription.backend === "OpenAICompatible" && (
+          <>
+            <label>
+              Server URL:
+              <input
+                type="text"
+                value={localConfig.transcription.compatible_base_url}
+                onChange={(e) => updateTranscription("compatible_base_url", e.target.value)}
+                placeholder="http://localhost:8000/v1"
+              />
+            </label>
+            <label>
+              API Key:
+              <input
+                type="password"
+                value={localConfig.transcription.compatible_api_key || ""}
+                onChange={(e) => updateTranscription("compatible_api_key", e.target.value || null)}
+                placeholder="Leave empty if not required"
+              />
+            </label>
+            <label>
+              Auth Header:
+              <input
+                type="text"
+                value={localConfig.transcription.compatible_auth_header}
+                onChange={(e) => updateTranscription("compatible_auth_header", e.target.value)}
+                placeholder="Authorization"
+              />
+            </label>
+            <label>
+              Model:
+              <input
+                type="text"
+                value={localConfig.transcription.model}
+                onChange={(e) => updateTranscription("model", e.target.value)}
+                placeholder="Systran/faster-whisper-small"
+              />
+            </label>
+            <label>
+              Timeout (seconds):
+              <input
+                type="number"
+                min={1}
+                value={localConfig.transcription.compatible_timeout_seconds}
+                onChange={(e) =>
+                  updateTranscription("compatible_timeout_seconds", Number(e.target.value))
+                }
+              />
+            </label>
+          </>
+        )}
+
         {localConfig.transcription.backend === "OpenAI" && (
           <>
             <label>
@@ -245,6 +335,51 @@ export default function SettingsPanel({ config, onUpdate, onClose }: SettingsPan
             )}
           </>
         )}
+
+        <label>
+          Language:
+          <input
+            type="text"
+            value={localConfig.transcription.language || ""}
+            onChange={(e) => updateTranscription("language", e.target.value || null)}
+            placeholder="auto"
+          />
+        </label>
+        <label>
+          Task:
+          <select
+            value={localConfig.transcription.task}
+            onChange={(e) => updateTranscription("task", e.target.value)}
+          >
+            <option value="Transcribe">Transcribe in the spoken language</option>
+            <option value="Translate">Translate to English</option>
+          </select>
+        </label>
+        <label>
+          Vocabulary (one term per line, most important first):
+          <textarea
+            rows={4}
+            value={localConfig.transcription.vocabulary.join("\n")}
+            onChange={(e) => updateTranscription("vocabulary", e.target.value.split("\n"))}
+            placeholder="Product names, colleagues, jargon..."
+          />
+        </label>
+        {Object.keys(localConfig.transcription.vocabulary_profiles).length > 0 && (
+          <label>
+            Vocabulary profile:
+            <select
+              value={localConfig.transcription.active_vocabulary_profile}
+              onChange={(e) => updateTranscription("active_vocabulary_profile", e.target.value)}
+            >
+              <option value="">None</option>
+              {Object.keys(localConfig.transcription.vocabulary_profiles).map((name) => (
+                <option key={name} value={name}>
+                  {name}
+                </option>
+              ))}
+            </select>
+          </label>
+        )}
       </section>
 
       <section className="settings-section">
@@ -21,6 +21,17 @@ describe("StatusIndicator", () => {
     expect(screen.getByText("Recording...")).toBeInTheDocument();
   });
 
+  it("shows paused status with recorded time", () => {
+    const state: RecordingState = {
+      type: "Paused",
+      data: { elapsed_ms: 12_500 },
+    };
+    const { container } = render(<StatusIndicator state={state} />);
+
+    expect(screen.getByText("Paused (12s)")).toBeInTheDocument();
+    expect(container.querySelector(".status-paused")).toBeInTheDocument();
+  });
+
   it("shows transcribing status when transcribing", () => {
     const state: RecordingState = {
       type: "Processing",
@@ -14,6 +14,10 @@ export default function StatusIndicator({ state }: StatusIndicatorProps) {
         const elapsed = Math.floor((Date.now() - state.data.started_at) / 1000);
         return `Recording... (${elapsed}s)`;
       }
+      case "Paused": {
+        const elapsed = Math.floor(state.data.elapsed_ms / 1000);
+        return `Paused (${elapsed}s)`;
+      }
       case "Processing":
         return `Processing: ${state.data.stage}`;
       case "Error":
@@ -27,6 +31,8 @@ export default function StatusIndicator({ state }: StatusIndicatorProps) {
         return "status-idle";
       case "Recording":
         return "status-recording";
+      case "Paused":
+        return "status-paused";
       case "Processing":
         return "status-processing";
       case "Error":
@@ -11,14 +11,54 @@ export const mockCommands = {
       channels: 1,
       bit_depth: 16,
       device_id: "default",
+      capture_mode: "Microphone",
+      monitor_device_id: "",
       vad_enabled: false,
+      vad_aggressiveness: 1,
+      vad_frame_duration_ms: 30,
+      vad_max_pause_ms: 1000,
+      auto_stop_enabled: false,
+      auto_stop_silence_ms: 1500,
       max_recording_duration_seconds: 300,
+      duration_warning_seconds: 30,
+      archive_recordings: false,
+      archive_dir: "",
+      pre_roll_enabled: false,
+      pre_roll_ms: 300,
+      reconnect_on_device_loss: true,
+      highpass_enabled: true,
+      highpass_cutoff_hz: 80,
+      noise_gate_enabled: false,
+      noise_gate_threshold_db: -50,
+      normalize_enabled: true,
+      normalize_mode: "Loudness",
+      normalize_peak_db: -1,
+      normalize_lufs: -20,
+      soft_clip_enabled: true,
     },
     transcription: {
       backend: "OpenAI",
       model: "whisper-1",
       language: null,
+      task: "Transcribe",
       openai_api_key: null,
+      openai_upload_format: "Flac",
+      faster_whisper_upload_format: "Wav",
+      compatible_base_url: "http://localhost:8000/v1",
+      compatible_api_key: null,
+      compatible_auth_header: "Authorization",
+      compatible_timeout_seconds: 300,
+      compatible_upload_format: "Flac",
+      whisper_cpp_url: "http://127.0.0.1:8080",
+      whisper_cpp_model_path: "",
+      whisper_cpp_temperature: 0,
+      chunk_seconds: 60,
+      chunk_overlap_ms: 1000,
+      chunk_concurrency: 3,
+      vocabulary: [],
+      vocabulary_profiles: {},
+      active_vocabulary_profile: "",
+      word_timestamps: false,
     },
     llm: {
       backend: "OpenAI",
@@ -43,8 +83,14 @@ export const mockCommands = {
       show_notifications: true,
       minimize_to_tray: true,
     },
+    privacy: {
+      keep_until_processed: false,
+      audio_encryption: false,
+      paranoid_mode: false,
+    },
   }),
   get_recording_state: () => ({ type: "Idle" }),
+  list_recoverable_recordings: () => [],
   check_docker_status: () => ({
     available: true,
     container_running: false,
@@ -3,11 +3,53 @@
 export type RecordingState =
   | { type: "Idle" }
   | { type: "Recording"; data: { started_at: number } }
+  | { type: "Paused"; data: { elapsed_ms: number } }
   | { type: "Processing"; data: { stage: ProcessingStage } }
   | { type: "Error"; data: { message: string } };
 
 export type ProcessingStage = "Transcribing" | "Rewriting" | "Injecting";
 
+export type AutoStopReason = "Silence" | "TimeLimit";
+
+// Payload of the "recording-auto-stopped" event
+export interface AutoStopEvent {
+  reason: AutoStopReason;
+}
+
+// Payload of the "recording-time-limit-approaching" event
+export interface TimeLimitEvent {
+  remaining_seconds: number;
+  max_seconds: number;
+}
+
+// Payload of the "audio-level" event, sent every 50 ms while recording
+export interface AudioLevel {
+  rms: number;
+  peak: number;
+  waveform: number[];
+}
+
+export interface ProcessedResult {
+  transcription: string;
+  cleaned_text: string;
+  language: string | null;
+  segments: Segment[];
+}
+
+export interface Segment {
+  start: number;
+  end: number;
+  text: string;
+  avg_logprob: number | null;
+  no_speech_prob: number | null;
+}
+
+export interface RecoverableRecording {
+  path: string;
+  recorded_at: number;
+  encrypted: boolean;
+}
+
 export interface AppConfig {
   audio: AudioConfig;
   transcription: TranscriptionConfig;
@@ -15,6 +57,7 @@ export interface AppConfig {
   injection: InjectionConfig;
   hotkeys: HotkeyConfig;
   ui: UIConfig;
+  privacy: PrivacyConfig;
 }
 
 export interface AudioConfig {
@@ -22,18 +65,66 @@ export interface AudioConfig {
   channels: number;
   bit_depth: number;
   device_id: string;
+  capture_mode: CaptureMode;
+  monitor_device_id: string;
   vad_enabled: boolean;
+  vad_aggressiveness: number;
+  vad_frame_duration_ms: number;
+  vad_max_pause_ms: number;
+  auto_stop_enabled: boolean;
+  auto_stop_silence_ms: number;
   max_recording_duration_seconds: number;
+  duration_warning_seconds: number;
+  archive_recordings: boolean;
+  archive_dir: string;
+  pre_roll_enabled: boolean;
+  pre_roll_ms: number;
+  reconnect_on_device_loss: boolean;
+  highpass_enabled: boolean;
+  highpass_cutoff_hz: number;
+  noise_gate_enabled: boolean;
+  noise_gate_threshold_db: number;
+  normalize_enabled: boolean;
+  normalize_mode: NormalizeMode;
+  normalize_peak_db: number;
+  normalize_lufs: number;
+  soft_clip_enabled: boolean;
 }
 
+export type CaptureMode = "Microphone" | "SystemAudio" | "Mixed";
+
+export type NormalizeMode = "Peak" | "Loudness";
+
 export interface TranscriptionConfig {
   backend: TranscriptionBackend;
   model: string;
   language: string | null;
+  task: TranscriptionTask;
   openai_api_key: string | null;
+  openai_upload_format: UploadFormat;
+  faster_whisper_upload_format: UploadFormat;
+  compatible_base_url: string;
+  compatible_api_key: string | null;
+  compatible_auth_header: string;
+  compatible_timeout_seconds: number;
+  compatible_upload_format: UploadFormat;
+  whisper_cpp_url: string;
+  whisper_cpp_model_path: string;
+  whisper_cpp_temperature: number;
+  chunk_seconds: number;
+  chunk_overlap_ms: number;
+  chunk_concurrency: number;
+  vocabulary: string[];
+  vocabulary_profiles: Record<string, string[]>;
+  active_vocabulary_profile: string;
+  word_timestamps: boolean;
 }
 
-export type TranscriptionBackend = "FasterWhisper" | "OpenAI";
+export type TranscriptionBackend = "FasterWhisper" | "OpenAI" | "OpenAICompatible" | "WhisperCpp";
+
+export type TranscriptionTask = "Transcribe" | "Translate";
+
+export type UploadFormat = "Wav" | "Flac" | "Opus";
 
 export interface LLMConfig {
   backend: LLMBackend;
@@ -68,6 +159,12 @@ export interface UIConfig {
 
 export type Theme = "Light" | "Dark" | "System";
 
+export interface PrivacyConfig {
+  keep_until_processed: boolean;
+  audio_encryption: boolean;
+  paranoid_mode: boolean;
+}
+
 export interface BackendStatus {
   name: string;
   available: boolean;
@@ -100,3 +197,17 @@ export interface OllamaModelInfo {
   modified_at: string;
   recommended: boolean;
 }
+
+export interface AudioDeviceInfo {
+  name: string;
+  is_default: boolean;
+  is_monitor: boolean;
+  supported_configs: SupportedConfigInfo[];
+}
+
+export interface SupportedConfigInfo {
+  channels: number;
+  min_sample_rate: number;
+  max_sample_rate: number;
+  sample_format: string;
+}